@@ -5,8 +5,13 @@ _rusmux() {
   cur="${COMP_WORDS[COMP_CWORD]}"
   prev="${COMP_WORDS[COMP_CWORD - 1]}"
 
-  commands="run stop debug edit delete new list copy doctor"
-  aliases="start kill ls cp"
+  commands="run open restart send exec logs stop status attach switch debug edit delete new templates list copy rename import export allow deny trusted doctor"
+  aliases="start kill ls cp mv"
+
+  if [[ "$prev" == "--config-dir" ]]; then
+    COMPREPLY=($(compgen -d -- "$cur"))
+    return 0
+  fi
 
   if [[ "$prev" == "rusmux" ]]; then
     COMPREPLY=($(compgen -W "$commands $aliases" -- "$cur"))
@@ -16,20 +21,138 @@ _rusmux() {
   projects=$(rusmux list 2>/dev/null)
 
   case "$prev" in
-  run | start | stop | kill | debug | edit | delete)
+  run | start)
+    if [[ "$cur" == -* ]]; then
+      COMPREPLY=($(compgen -W "--attach --no-attach --read-only --detach-others --wait --trust --tag --fail-fast" -- "$cur"))
+    else
+      COMPREPLY=($(compgen -W "$projects" -- "$cur"))
+    fi
+    return 0
+    ;;
+  open)
+    if [[ "$cur" == -* ]]; then
+      COMPREPLY=($(compgen -W "--attach --no-attach --read-only --detach-others --wait --trust" -- "$cur"))
+    else
+      COMPREPLY=($(compgen -d -- "$cur"))
+    fi
+    return 0
+    ;;
+  restart)
+    if [[ "$cur" == --* ]]; then
+      COMPREPLY=($(compgen -W "--window --trust" -- "$cur"))
+    else
+      COMPREPLY=($(compgen -W "$projects" -- "$cur"))
+    fi
+    return 0
+    ;;
+  send | exec)
+    if [[ "$cur" == --* ]]; then
+      COMPREPLY=($(compgen -W "--literal --no-enter --wait-for-output --timeout --window" -- "$cur"))
+    else
+      COMPREPLY=($(compgen -W "$projects" -- "$cur"))
+    fi
+    return 0
+    ;;
+  logs)
+    if [[ "$cur" == -* ]]; then
+      COMPREPLY=($(compgen -W "--lines --follow" -- "$cur"))
+    else
+      COMPREPLY=($(compgen -W "$projects" -- "$cur"))
+    fi
+    return 0
+    ;;
+  --format)
+    COMPREPLY=($(compgen -W "bash json tmux-conf" -- "$cur"))
+    return 0
+    ;;
+  debug)
+    if [[ "$cur" == --* ]]; then
+      COMPREPLY=($(compgen -W "--format --base-index --pane-base-index --tmux-version" -- "$cur"))
+    else
+      COMPREPLY=($(compgen -W "$projects" -- "$cur"))
+    fi
+    return 0
+    ;;
+  list | ls)
+    COMPREPLY=($(compgen -W "--tag --long --json" -- "$cur"))
+    return 0
+    ;;
+  stop | kill)
+    if [[ "$cur" == --* ]]; then
+      COMPREPLY=($(compgen -W "--trust --tag --fail-fast" -- "$cur"))
+    else
+      COMPREPLY=($(compgen -W "$projects" -- "$cur"))
+    fi
+    return 0
+    ;;
+  attach)
+    if [[ "$cur" == --* ]]; then
+      COMPREPLY=($(compgen -W "--view --window --pane --client --read-only --detach-others" -- "$cur"))
+    else
+      COMPREPLY=($(compgen -W "$projects" -- "$cur"))
+    fi
+    return 0
+    ;;
+  switch)
+    COMPREPLY=($(compgen -W "--next --previous" -- "$cur"))
+    return 0
+    ;;
+  status | edit | delete)
     COMPREPLY=($(compgen -W "$projects" -- "$cur"))
     return 0
     ;;
+  allow | deny)
+    COMPREPLY=($(compgen -f -- "$cur"))
+    return 0
+    ;;
+  --template | -t | show)
+    COMPREPLY=($(compgen -W "$(rusmux templates list 2>/dev/null)" -- "$cur"))
+    return 0
+    ;;
+  templates)
+    COMPREPLY=($(compgen -W "list ls show edit" -- "$cur"))
+    return 0
+    ;;
   new)
     if [[ "$cur" == --* ]]; then
-      COMPREPLY=($(compgen -W "--blank" -- "$cur"))
+      COMPREPLY=($(compgen -W "--blank --template --var" -- "$cur"))
     else
       COMPREPLY=($(compgen -W "$projects" -- "$cur"))
     fi
     return 0
     ;;
-  copy | cp)
-    if [[ ${COMP_CWORD} -eq 2 ]]; then
+  --from)
+    COMPREPLY=($(compgen -W "tmuxp teamocil smug" -- "$cur"))
+    return 0
+    ;;
+  import)
+    if [[ "$cur" == --* ]]; then
+      COMPREPLY=($(compgen -W "--from" -- "$cur"))
+    else
+      COMPREPLY=($(compgen -f -- "$cur"))
+    fi
+    return 0
+    ;;
+  --to)
+    COMPREPLY=($(compgen -W "tmuxp zellij" -- "$cur"))
+    return 0
+    ;;
+  export)
+    if [[ "$cur" == --* ]]; then
+      COMPREPLY=($(compgen -W "--to" -- "$cur"))
+    else
+      COMPREPLY=($(compgen -W "$projects" -- "$cur"))
+    fi
+    return 0
+    ;;
+  --root)
+    COMPREPLY=($(compgen -d -- "$cur"))
+    return 0
+    ;;
+  copy | cp | rename | mv)
+    if [[ "$cur" == --* ]]; then
+      COMPREPLY=($(compgen -W "--root" -- "$cur"))
+    elif [[ ${COMP_CWORD} -eq 2 ]]; then
       COMPREPLY=($(compgen -W "$projects" -- "$cur"))
     fi
     return 0
@@ -11,27 +11,106 @@ complete -c rusmux -f # Disable file completion by default
 # Subcommands
 complete -c rusmux -n __fish_use_subcommand -a run -d "Run the project's commands"
 complete -c rusmux -n __fish_use_subcommand -a start -d "Alias for run"
+complete -c rusmux -n __fish_use_subcommand -a restart -d "Restart the project's session, or a single window of it"
+complete -c rusmux -n __fish_use_subcommand -a open -d "Run a session for a directory"
+complete -c rusmux -n __fish_use_subcommand -a send -d "Send keys to a pane of a running project"
+complete -c rusmux -n __fish_use_subcommand -a exec -d "Run a command in all the panes of a running project"
+complete -c rusmux -n __fish_use_subcommand -a logs -d "Print the output of a pane of a running project"
 complete -c rusmux -n __fish_use_subcommand -a stop -d "Stop the project's session"
 complete -c rusmux -n __fish_use_subcommand -a kill -d "Alias for stop"
+complete -c rusmux -n __fish_use_subcommand -a status -d "Show whether the project's session is running"
+complete -c rusmux -n __fish_use_subcommand -a attach -d "Attach to the project's running session"
+complete -c rusmux -n __fish_use_subcommand -a switch -d "Switch to another running project's session"
 complete -c rusmux -n __fish_use_subcommand -a debug -d "Output shell commands for a project"
 complete -c rusmux -n __fish_use_subcommand -a edit -d "Edit an existing project"
 complete -c rusmux -n __fish_use_subcommand -a delete -d "Delete an existing project"
 complete -c rusmux -n __fish_use_subcommand -a new -d "Create a new project"
+complete -c rusmux -n __fish_use_subcommand -a templates -d "Manage the project templates"
 complete -c rusmux -n __fish_use_subcommand -a list -d "List all projects in the config directory"
 complete -c rusmux -n __fish_use_subcommand -a ls -d "Alias for list"
 complete -c rusmux -n __fish_use_subcommand -a copy -d "Copy an existing project to a new one and edit it"
 complete -c rusmux -n __fish_use_subcommand -a cp -d "Alias for copy"
+complete -c rusmux -n __fish_use_subcommand -a rename -d "Rename a project, and its session if running"
+complete -c rusmux -n __fish_use_subcommand -a mv -d "Alias for rename"
+complete -c rusmux -n __fish_use_subcommand -a import -d "Import a project from another session manager's config file"
+complete -c rusmux -n __fish_use_subcommand -a export -d "Export a project to another session manager's format"
+complete -c rusmux -n __fish_use_subcommand -a allow -d "Trust a project file outside the config directory"
+complete -c rusmux -n __fish_use_subcommand -a deny -d "Deny running a project file outside the config directory"
+complete -c rusmux -n __fish_use_subcommand -a trusted -d "List the allowed and denied project files"
 complete -c rusmux -n __fish_use_subcommand -a doctor -d "Check your environment's configuration"
 
 # Command-specific completions using rusmux list output
 # run/start - requires project argument from rusmux list
 complete -c rusmux -n "__fish_seen_subcommand_from run start" -r -a "(__rusmux_projects)"
+complete -c rusmux -n "__fish_seen_subcommand_from run start" -l attach -d "Attach to the session once it's ready"
+complete -c rusmux -n "__fish_seen_subcommand_from run start" -l no-attach -d "Create the session detached"
+complete -c rusmux -n "__fish_seen_subcommand_from run start" -s r -l read-only -d "Attach in read-only mode"
+complete -c rusmux -n "__fish_seen_subcommand_from run start" -s d -l detach-others -d "Detach other clients"
+complete -c rusmux -n "__fish_seen_subcommand_from run start" -l wait -r -d "Wait for the pane's commands to exit"
+complete -c rusmux -n "__fish_seen_subcommand_from run start" -l trust -d "Don't check the project file against the trust store"
+complete -c rusmux -n "__fish_seen_subcommand_from run start" -l tag -x -d "Run the projects with the tag, detached"
+complete -c rusmux -n "__fish_seen_subcommand_from run start" -l fail-fast -d "Stop at the first project failing to start"
+
+# open - requires a directory
+complete -c rusmux -n "__fish_seen_subcommand_from open" -x -a "(__fish_complete_directories)"
+complete -c rusmux -n "__fish_seen_subcommand_from open" -l attach -d "Attach to the session once it's ready"
+complete -c rusmux -n "__fish_seen_subcommand_from open" -l no-attach -d "Create the session detached"
+complete -c rusmux -n "__fish_seen_subcommand_from open" -s r -l read-only -d "Attach in read-only mode"
+complete -c rusmux -n "__fish_seen_subcommand_from open" -s d -l detach-others -d "Detach other clients"
+complete -c rusmux -n "__fish_seen_subcommand_from open" -l wait -r -d "Wait for the pane's commands to exit"
+complete -c rusmux -n "__fish_seen_subcommand_from open" -l trust -d "Don't check the project file against the trust store"
+
+# restart - requires project argument from rusmux list, has --window option
+complete -c rusmux -n "__fish_seen_subcommand_from restart" -r -a "(__rusmux_projects)"
+complete -c rusmux -n "__fish_seen_subcommand_from restart" -l window -r -d "Re-create only this window"
+complete -c rusmux -n "__fish_seen_subcommand_from restart" -l trust -d "Don't check the project file against the trust store"
+
+# send/exec - requires project argument from rusmux list
+complete -c rusmux -n "__fish_seen_subcommand_from send exec" -r -a "(__rusmux_projects)"
+complete -c rusmux -n "__fish_seen_subcommand_from send exec" -l literal -d "Send the keys literally"
+complete -c rusmux -n "__fish_seen_subcommand_from send exec" -l no-enter -d "Don't press Enter after the keys"
+complete -c rusmux -n "__fish_seen_subcommand_from send exec" -l wait-for-output -r -d "Wait until the output matches the regex"
+complete -c rusmux -n "__fish_seen_subcommand_from send exec" -l timeout -r -d "Seconds to wait for the output"
+complete -c rusmux -n "__fish_seen_subcommand_from exec" -l window -r -d "Run only in the panes of this window"
+
+# logs - requires project argument from rusmux list
+complete -c rusmux -n "__fish_seen_subcommand_from logs" -r -a "(__rusmux_projects)"
+complete -c rusmux -n "__fish_seen_subcommand_from logs" -s n -l lines -r -d "Number of lines to print"
+complete -c rusmux -n "__fish_seen_subcommand_from logs" -s f -l follow -d "Keep printing new output"
 
 # stop/kill - requires project argument from rusmux list
 complete -c rusmux -n "__fish_seen_subcommand_from stop kill" -r -a "(__rusmux_projects)"
+complete -c rusmux -n "__fish_seen_subcommand_from stop kill" -l trust -d "Don't check the project file against the trust store"
+complete -c rusmux -n "__fish_seen_subcommand_from stop kill" -l tag -x -d "Stop the running projects with the tag"
+complete -c rusmux -n "__fish_seen_subcommand_from stop kill" -l fail-fast -d "Stop at the first project failing to stop"
+
+# status - requires project argument from rusmux list
+complete -c rusmux -n "__fish_seen_subcommand_from status" -r -a "(__rusmux_projects)"
+
+# attach - requires project argument from rusmux list
+complete -c rusmux -n "__fish_seen_subcommand_from attach" -r -a "(__rusmux_projects)"
+complete -c rusmux -n "__fish_seen_subcommand_from attach" -l view -d "Attach to a new view of the session"
+complete -c rusmux -n "__fish_seen_subcommand_from attach" -l window -r -d "Select this window"
+complete -c rusmux -n "__fish_seen_subcommand_from attach" -l pane -r -d "Select this pane of the window"
+complete -c rusmux -n "__fish_seen_subcommand_from attach" -l client -r -d "Switch this client to the session"
+complete -c rusmux -n "__fish_seen_subcommand_from attach" -s r -l read-only -d "Attach in read-only mode"
+complete -c rusmux -n "__fish_seen_subcommand_from attach" -s d -l detach-others -d "Detach other clients"
+
+# switch - has --next and --previous options
+complete -c rusmux -n "__fish_seen_subcommand_from switch" -l next -d "Switch to the next running project"
+complete -c rusmux -n "__fish_seen_subcommand_from switch" -l previous -d "Switch to the previous running project"
+
+# list/ls - filter by tags, show details
+complete -c rusmux -n "__fish_seen_subcommand_from list ls" -l tag -x -d "Only the projects with the tag"
+complete -c rusmux -n "__fish_seen_subcommand_from list ls" -s l -l long -d "Show the projects' details"
+complete -c rusmux -n "__fish_seen_subcommand_from list ls" -l json -d "Print the projects' details as JSON"
 
 # debug - requires project argument from rusmux list
 complete -c rusmux -n "__fish_seen_subcommand_from debug" -r -a "(__rusmux_projects)"
+complete -c rusmux -n "__fish_seen_subcommand_from debug" -l format -r -a "bash json tmux-conf" -d "Output format"
+complete -c rusmux -n "__fish_seen_subcommand_from debug" -l base-index -x -d "Tmux base-index option"
+complete -c rusmux -n "__fish_seen_subcommand_from debug" -l pane-base-index -x -d "Tmux pane-base-index option"
+complete -c rusmux -n "__fish_seen_subcommand_from debug" -l tmux-version -x -d "Tmux version"
 
 # edit - requires project argument from rusmux list
 complete -c rusmux -n "__fish_seen_subcommand_from edit" -r -a "(__rusmux_projects)"
@@ -42,10 +121,31 @@ complete -c rusmux -n "__fish_seen_subcommand_from delete" -r -a "(__rusmux_proj
 # new - requires a new project name (no specific completions), has --blank flag
 complete -c rusmux -n "__fish_seen_subcommand_from new" -r -d "New project name"
 complete -c rusmux -n "__fish_seen_subcommand_from new" -l blank -d "Don't use a template for the file"
+complete -c rusmux -n "__fish_seen_subcommand_from new" -s t -l template -x -a "(rusmux templates list 2>/dev/null)" -d "The template to use"
+complete -c rusmux -n "__fish_seen_subcommand_from new" -l var -x -d "Set a variable of the template"
+
+# templates - list, show or edit the templates
+complete -c rusmux -n "__fish_seen_subcommand_from templates; and not __fish_seen_subcommand_from list ls show edit" -a "list ls show edit"
+complete -c rusmux -n "__fish_seen_subcommand_from templates; and __fish_seen_subcommand_from show edit" -x -a "(rusmux templates list 2>/dev/null)"
 
 # copy - requires existing project from rusmux list and new project name
-complete -c rusmux -n "__fish_seen_subcommand_from copy cp" -r -a "(__rusmux_projects)" -d "Existing project name from rusmux list"
-complete -c rusmux -n "__fish_seen_subcommand_from copy cp; and __fish_prev_arg_in (__rusmux_projects)" -r -d "New project name"
+complete -c rusmux -n "__fish_seen_subcommand_from copy cp rename mv" -r -a "(__rusmux_projects)" -d "Existing project name from rusmux list"
+complete -c rusmux -n "__fish_seen_subcommand_from copy cp rename mv; and __fish_prev_arg_in (__rusmux_projects)" -r -d "New project name"
+complete -c rusmux -n "__fish_seen_subcommand_from copy cp rename mv" -l root -x -a "(__fish_complete_directories)" -d "Set the new project root"
+
+# import - requires a file, has --from for the file's format
+complete -c rusmux -n "__fish_seen_subcommand_from import" -r -F
+complete -c rusmux -n "__fish_seen_subcommand_from import" -l from -x -a "tmuxp teamocil smug" -d "Format of the file"
+
+# export - requires project argument from rusmux list, has --to for the format
+complete -c rusmux -n "__fish_seen_subcommand_from export" -r -a "(__rusmux_projects)"
+complete -c rusmux -n "__fish_seen_subcommand_from export" -l to -x -a "tmuxp zellij" -d "Format to export to"
+
+# allow/deny - a project file, defaults to the local one
+complete -c rusmux -n "__fish_seen_subcommand_from allow deny" -r -F
+
+# Global options
+complete -c rusmux -l config-dir -x -a "(__fish_complete_directories)" -d "Search this directory for projects first"
 
 # Version and help
 complete -c rusmux -s v -l version -d "Show version information"
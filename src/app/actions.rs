@@ -1,61 +1,616 @@
 //! Handles the command requested by the CLI.
-use super::config::{self, get_projects};
+use super::cli::{DebugFormat, ExportFormat, ImportFormat, RunArgs, SendArgs, TmuxArgs};
+use super::config::{self, get_projects, ConfigProject};
+use super::groups::{group_name, Group};
+use super::history::History;
+use super::settings::{Settings, SETTINGS_FILE};
+use super::templates::Template;
+use super::trust::{Trust, TrustStatus, TrustStore};
 use crate::error::AppError;
-use crate::project_config::ProjectConfig;
-use crate::tmux::{self, TmuxProject};
+use crate::interop::{smug, teamocil, tmuxp, zellij};
+use crate::project_config::window::DEFAULT_LAYOUT;
+use crate::project_config::{ProjectConfig, ProjectRewrite};
+use crate::tmux::{
+    run_hook, view_names, AttachOptions, SendOptions, TmuxProject, TmuxServer, TmuxSettings,
+};
 use colored::{ColoredString, Colorize};
-use dialoguer::Confirm;
+use dialoguer::{Confirm, FuzzySelect, Select};
+use regex::Regex;
+use serde::Serialize;
+use std::fs::{create_dir_all, write, File};
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf, MAIN_SEPARATOR};
+use std::time::Duration;
 use std::{env, fs::copy, process::Command};
-use std::{fs::remove_file, io::prelude::*};
-use std::{fs::File, path::Path};
+use std::{fs::read_to_string, fs::remove_file, io::prelude::*};
 use which::which;
 
-/// The default template used when create a new project.
-macro_rules! default_template {
-    () => {
-        "project_name: {}
-# project_root: ~/src/project_path
-# on_project_start:
-#   - sudo systemctl start postgresql
-# pre_window:
-#   - workon dummy
-# windows:
-#   - editor: vim
-#   - shells:
-#       layout: main-vertical
-#       panes:
-#         - #
-#         - grunt serve
-"
-    };
-}
-
-/// List the projects in the configuration directory.
-pub(crate) fn list_projects() -> Result<(), AppError> {
+/// Options of the `list` command.
+pub struct ListOptions<'a> {
+    /// Only the projects with all the tags.
+    pub tags: &'a [String],
+    /// Show the details of the projects, see [`print_project_table`].
+    pub long: bool,
+    /// Print the details of the projects as JSON.
+    pub json: bool,
+}
+
+/// List the projects in the configuration directories, and the local project
+/// (if found).
+///
+/// On a terminal shows the projects as a tree, see [`print_project_tree`].
+/// Otherwise (e.g. for the shell completions) only prints the names of the
+/// projects which can be run.
+///
+/// With any of the [`ListOptions`], lists the details of the projects which
+/// can be run instead.
+pub(crate) fn list_projects(options: &ListOptions) -> Result<(), AppError> {
+    if !options.tags.is_empty() || options.long || options.json {
+        return list_project_details(options);
+    }
     let projects = get_projects()?;
-    for project in projects {
-        println!("{project}");
+    let is_terminal = io::stdout().is_terminal();
+    if is_terminal {
+        print_project_tree(&projects);
+    } else {
+        for project in projects.iter().filter(|p| p.shadowed_by.is_none()) {
+            println!("{}", project.name);
+        }
+    }
+    // The local project is listed by its path, which can be used as the
+    // project's argument (e.g. by the shell completions)
+    let current_dir = env::current_dir().map_err(AppError::CurrentDir)?;
+    if let Some(path) = config::find_local_project(&current_dir) {
+        if io::stdout().is_terminal() {
+            println!("{} {}", path.display(), "(local)".dimmed());
+        } else {
+            println!("{}", path.display());
+        }
     }
 
     Ok(())
 }
 
-/// Parses the project file and prints the shell commands for session creation.
-pub(crate) fn debug_project(project_name: &str) -> Result<(), AppError> {
-    let entries = config::get_project_yaml(project_name)?;
-    let project = ProjectConfig::try_from(entries)?;
-    let tmux = TmuxProject::new(&project)?;
-    println!("{tmux}");
+/// A project which can be run, with its parsed file.
+struct ProjectInfo {
+    /// The project's name in the configuration directories, or the path of
+    /// the local project.
+    name: String,
+    path: PathBuf,
+    /// Whether it's the local project.
+    local: bool,
+    /// The parsed project file, `None` if it's invalid.
+    config: Option<ProjectConfig>,
+}
+
+impl ProjectInfo {
+    /// Whether the project has all the tags.
+    fn has_tags(&self, tags: &[String]) -> bool {
+        let project_tags = self
+            .config
+            .as_ref()
+            .and_then(|config| config.tags.as_deref())
+            .unwrap_or_default();
+        tags.iter().all(|tag| project_tags.contains(tag))
+    }
+
+    /// Returns where to load the project from.
+    fn source(&self) -> ProjectSource {
+        match self.local {
+            true => ProjectSource::Local(self.path.clone()),
+            false => ProjectSource::File(self.path.clone()),
+        }
+    }
+}
+
+/// Loads the projects in the configuration directories which aren't
+/// shadowed, and optionally the local project (if found).
+fn load_projects(with_local: bool) -> Result<Vec<ProjectInfo>, AppError> {
+    let mut projects = vec![];
+    for project in get_projects()? {
+        if project.shadowed_by.is_none() {
+            let path = config::get_project_path(&project.name)?;
+            projects.push((project.name, path, false));
+        }
+    }
+    if with_local {
+        let current_dir = env::current_dir().map_err(AppError::CurrentDir)?;
+        if let Some(path) = config::find_local_project(&current_dir) {
+            projects.push((path.display().to_string(), path, true));
+        }
+    }
+    Ok(projects
+        .into_iter()
+        .map(|(name, path, local)| {
+            let config = config::read_project_file(&path)
+                .ok()
+                .and_then(|contents| ProjectConfig::try_from(contents).ok());
+            ProjectInfo {
+                name,
+                path,
+                local,
+                config,
+            }
+        })
+        .collect())
+}
+
+/// Loads the projects with all the tags, see [`load_projects`]. Fails if
+/// there are none.
+fn load_tagged_projects(tags: &[String]) -> Result<Vec<ProjectInfo>, AppError> {
+    let projects: Vec<_> = load_projects(true)?
+        .into_iter()
+        .filter(|project| project.config.is_some() && project.has_tags(tags))
+        .collect();
+    if projects.is_empty() {
+        return Err(AppError::NoTaggedProjects(tags.join(", ")));
+    }
+    Ok(projects)
+}
+
+/// The details of a project, listed by `list --json`.
+#[derive(Serialize)]
+struct ProjectDetails<'a> {
+    name: &'a str,
+    path: &'a Path,
+    /// `false` if the project file is invalid, without the details below.
+    valid: bool,
+    project_name: Option<&'a str>,
+    description: Option<&'a str>,
+    root: Option<&'a str>,
+    windows: usize,
+    tags: &'a [String],
+    running: bool,
+}
+
+impl<'a> ProjectDetails<'a> {
+    fn new(project: &'a ProjectInfo, running: &[String]) -> Self {
+        let config = project.config.as_ref();
+        Self {
+            name: &project.name,
+            path: &project.path,
+            valid: config.is_some(),
+            project_name: config.map(|c| c.project_name.as_str()),
+            description: config.and_then(|c| c.description.as_deref()),
+            root: config.and_then(|c| c.project_root.as_deref()),
+            windows: config
+                .and_then(|c| c.windows.as_ref())
+                .map_or(0, |windows| windows.len()),
+            tags: config.and_then(|c| c.tags.as_deref()).unwrap_or_default(),
+            running: config.is_some_and(|c| running.contains(&c.project_name)),
+        }
+    }
+}
+
+/// Lists the projects which can be run (see [`load_projects`]) with the
+/// [`ListOptions`].
+fn list_project_details(options: &ListOptions) -> Result<(), AppError> {
+    let projects: Vec<_> = load_projects(true)?
+        .into_iter()
+        .filter(|project| project.has_tags(options.tags))
+        .collect();
+    let running = TmuxServer::get().session_names();
+    let details: Vec<_> = projects
+        .iter()
+        .map(|project| ProjectDetails::new(project, &running))
+        .collect();
+
+    if options.json {
+        let json = serde_json::to_string_pretty(&details).map_err(AppError::ListSerialize)?;
+        println!("{json}");
+    } else if options.long {
+        print_project_table(&details);
+    } else {
+        details
+            .iter()
+            .for_each(|project| println!("{}", project.name));
+    }
     Ok(())
 }
 
-/// Parses the project file, runs the commands to create the tmux session.
-pub fn run_project(project_name: &str) -> Result<(), AppError> {
-    println!("Starting project {project_name}");
+/// Prints the details of the projects as a table: name, whether the session
+/// is running, number of windows, root, tags and description.
+fn print_project_table(details: &[ProjectDetails]) {
+    let rows: Vec<[String; 6]> = details
+        .iter()
+        .map(|project| {
+            if !project.valid {
+                return [
+                    project.name.to_string(),
+                    "invalid".into(),
+                    String::new(),
+                    String::new(),
+                    String::new(),
+                    String::new(),
+                ];
+            }
+            [
+                project.name.to_string(),
+                if project.running { "running" } else { "-" }.into(),
+                project.windows.to_string(),
+                project.root.unwrap_or("-").to_string(),
+                project.tags.join(","),
+                project.description.unwrap_or_default().to_string(),
+            ]
+        })
+        .collect();
+    let header = ["NAME", "STATUS", "WINDOWS", "ROOT", "TAGS", "DESCRIPTION"].map(String::from);
+    let mut widths = [0; 6];
+    for row in std::iter::once(&header).chain(&rows) {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    for row in std::iter::once(&header).chain(&rows) {
+        let line = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:width$}"))
+            .collect::<Vec<_>>()
+            .join("  ");
+        println!("{}", line.trim_end());
+    }
+}
+
+/// Splits a project's name to its namespace and base name.
+fn split_namespace(name: &str) -> (Vec<&str>, &str) {
+    let mut parts: Vec<&str> = name.split('/').collect();
+    let leaf = parts.pop().unwrap_or_default();
+    (parts, leaf)
+}
+
+/// Prints the projects as a tree of their namespaces, with the directory of
+/// each project, flagging the ones shadowed by a project with the same name.
+fn print_project_tree(projects: &[ConfigProject]) {
+    // Namespaces are indented by 2 spaces
+    let width = projects
+        .iter()
+        .map(|project| {
+            let (namespace, leaf) = split_namespace(&project.name);
+            namespace.len() * 2 + leaf.len()
+        })
+        .max()
+        .unwrap_or(0);
+
+    let mut previous: Vec<&str> = vec![];
+    for project in projects {
+        let (namespace, leaf) = split_namespace(&project.name);
+        let common = previous
+            .iter()
+            .zip(&namespace)
+            .take_while(|(a, b)| a == b)
+            .count();
+        for (depth, part) in namespace.iter().enumerate().skip(common) {
+            println!("{}{}", "  ".repeat(depth), format!("{part}/").bold());
+        }
+
+        let indent = "  ".repeat(namespace.len());
+        let leaf_width = width - indent.len();
+        let dir = project.dir.display().to_string().dimmed();
+        match &project.shadowed_by {
+            None => println!("{indent}{leaf:leaf_width$}  {dir}"),
+            Some(by) => println!(
+                "{indent}{leaf:leaf_width$}  {dir} {}",
+                format!("(shadowed by {})", by.display()).yellow()
+            ),
+        }
+        previous = namespace;
+    }
+}
+
+/// Whether prompts can be shown, i.e. running in a terminal.
+pub(crate) fn can_prompt() -> bool {
+    io::stdin().is_terminal() && io::stderr().is_terminal()
+}
+
+/// Shows a fuzzy picker of the projects in the configuration directories,
+/// ordered by the most recently used (see [`History`]), and returns the
+/// picked project's file. Each project is annotated with whether its session
+/// is running, its root and description.
+fn pick_project() -> Result<PathBuf, AppError> {
+    let history = History::load()?;
+    let mut entries: Vec<_> = load_projects(false)?
+        .into_iter()
+        .map(|project| {
+            let last_used = project
+                .path
+                .canonicalize()
+                .ok()
+                .and_then(|canonical| history.last_used(&canonical));
+            (last_used, project)
+        })
+        .collect();
+    if entries.is_empty() {
+        return Err(AppError::NoProjects);
+    }
+    // Stable, keeping the projects which were never used ordered by name
+    entries.sort_by_key(|(last_used, _)| std::cmp::Reverse(*last_used));
+
+    let running = TmuxServer::get().session_names();
+    let width = entries
+        .iter()
+        .map(|(_, project)| project.name.len())
+        .max()
+        .unwrap_or(0);
+    let items: Vec<String> = entries
+        .iter()
+        .map(|(_, ProjectInfo { name, config, .. })| {
+            let Some(config) = config else {
+                return name.clone();
+            };
+            let status = match running.contains(&config.project_name) {
+                true => "running",
+                false => "",
+            };
+            let mut item = format!(
+                "{name:width$}  {status:7}  {}",
+                config.project_root.as_deref().unwrap_or_default()
+            );
+            if let Some(description) = &config.description {
+                item = format!("{item}  {description}");
+            }
+            item.trim_end().to_string()
+        })
+        .collect();
+
+    let selected = FuzzySelect::new()
+        .with_prompt("Project")
+        .items(&items)
+        .default(0)
+        .interact_opt()
+        .map_err(AppError::Prompt)?
+        .ok_or(AppError::NoProjectSelected)?;
+    Ok(entries.swap_remove(selected).1.path)
+}
+
+/// Parses the project file and prints the commands for session creation,
+/// formatted as a shell script, JSON or tmux configuration.
+pub(crate) fn debug_project(
+    project_name: Option<&str>,
+    format: DebugFormat,
+    tmux_args: &TmuxArgs,
+) -> Result<(), AppError> {
+    let project = load_project_or_local(project_name)?;
+    let settings = TmuxSettings {
+        base_index: tmux_args.base_index,
+        pane_base_index: tmux_args.pane_base_index,
+        version: tmux_args.tmux_version,
+    };
+    let tmux = TmuxProject::with_settings(&project, &settings);
+    match format {
+        DebugFormat::Bash => println!("{tmux}"),
+        DebugFormat::Json => println!("{}", tmux.to_json()?),
+        DebugFormat::TmuxConf => println!("{}", tmux.to_tmux_conf()),
+    }
+    Ok(())
+}
+
+/// Where a project is loaded from.
+enum ProjectSource {
+    /// A project file, by its name in the configuration directory or its path.
+    File(PathBuf),
+    /// A local project file, its `project_root` defaults to the file's
+    /// directory.
+    Local(PathBuf),
+    /// A directory without a local project file, its project is generated
+    /// from the directory settings.
+    Directory(PathBuf),
+}
+
+impl ProjectSource {
+    /// Resolves the project by its name or file path. A path to a directory
+    /// (e.g. `.`) resolves to the directory's project, see
+    /// [`ProjectSource::directory`]. Without a project name resolves to the
+    /// local project, see [`get_local_project_path`].
+    fn resolve(project_name: Option<&str>) -> Result<Self, AppError> {
+        let Some(project_name) = project_name else {
+            return Ok(Self::Local(get_local_project_path()?));
+        };
+        let path = Path::new(project_name);
+        let is_path = matches!(project_name, "." | "..") || project_name.contains(MAIN_SEPARATOR);
+        let file_path = config::get_project_path(project_name)?;
+        // A namespaced project (e.g. `work/api`) takes precedence
+        if is_path && path.is_dir() && !file_path.is_file() {
+            return Self::directory(path);
+        }
+        Ok(Self::File(file_path))
+    }
+
+    /// Same as [`ProjectSource::resolve`], without a project name and a local
+    /// project file shows the project picker on a terminal, see
+    /// [`pick_project`].
+    fn resolve_or_pick(project_name: Option<&str>) -> Result<Self, AppError> {
+        match Self::resolve(project_name) {
+            Err(AppError::LocalProjectNotFound(_)) if project_name.is_none() && can_prompt() => {
+                Ok(Self::File(pick_project()?))
+            }
+            result => result,
+        }
+    }
+
+    /// Resolves the project of a directory, its local project file if it
+    /// exists, otherwise generated from the directory settings.
+    fn directory(dir: &Path) -> Result<Self, AppError> {
+        let dir = dir
+            .canonicalize()
+            .map_err(|_| AppError::DirectoryNotFound(dir.to_path_buf()))?;
+        Ok(match config::get_local_project_file(&dir) {
+            Some(local_file) => Self::Local(local_file),
+            None => Self::Directory(dir),
+        })
+    }
+
+    /// The project file which has to be trusted before running it, one
+    /// outside the configuration directory, see [`trust`](super::trust).
+    fn untrusted_file(&self) -> Option<&Path> {
+        match self {
+            Self::File(path) if !config::is_config_file(path) => Some(path),
+            Self::Local(path) => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Loads the project, with the defaults of the global settings.
+    fn load(&self) -> Result<ProjectConfig, AppError> {
+        match self {
+            Self::File(path) | Self::Local(path) => {
+                self.parse(path, &config::read_project_file(path)?)
+            }
+            Self::Directory(dir) => {
+                let settings = Settings::get();
+                let project = settings.directory.clone().project(dir);
+                Ok(project.with_defaults(&settings.defaults))
+            }
+        }
+    }
+
+    /// Loads the project for running (or opening) it, see
+    /// [`ProjectSource::load_trusted`], and records the use of its file in the
+    /// [`History`]. Stopping or restarting a project doesn't record it.
+    fn load_to_run(&self, trust: bool) -> Result<ProjectConfig, AppError> {
+        let project = self.load_trusted(trust)?;
+        if let Self::File(path) | Self::Local(path) = self {
+            History::record(path);
+        }
+        Ok(project)
+    }
+
+    /// Loads the project, checking its file against the trust store first
+    /// unless `trust` is set. An untrusted file fails, after printing the
+    /// commands it runs.
+    fn load_trusted(&self, trust: bool) -> Result<ProjectConfig, AppError> {
+        let Some(path) = self.untrusted_file().filter(|_| !trust) else {
+            return self.load();
+        };
+        let contents = config::read_project_file(path)?;
+        let project = self.parse(path, &contents)?;
+        let canonical = canonicalize_project_file(path)?;
+        let error = match TrustStore::load()?.check(&canonical, contents.as_bytes()) {
+            Trust::Allowed => return Ok(project),
+            Trust::Denied => return Err(AppError::ProjectDenied(canonical)),
+            Trust::Unknown => AppError::ProjectNotTrusted(canonical),
+            Trust::Changed => AppError::ProjectChanged(canonical),
+        };
+        eprintln!("{}", "The project runs these commands:".yellow());
+        for (option, command) in project.commands() {
+            eprintln!("  {} {command}", format!("{option}:").dimmed());
+        }
+        Err(error)
+    }
+
+    /// Parses the content of the project file, applying the defaults of the
+    /// global settings.
+    fn parse(&self, path: &Path, contents: &str) -> Result<ProjectConfig, AppError> {
+        let entries = config::parse_project_yaml(path, contents)?;
+        let mut project = ProjectConfig::try_from(entries)?;
+        if let (Self::Local(_), Some(dir)) = (self, path.parent()) {
+            project
+                .project_root
+                .get_or_insert_with(|| dir.to_string_lossy().into_owned());
+        }
+        Ok(project.with_defaults(&Settings::get().defaults))
+    }
+}
+
+/// Returns the local project file, searched from the current directory, see
+/// [`config::find_local_project`].
+fn get_local_project_path() -> Result<PathBuf, AppError> {
+    let current_dir = env::current_dir().map_err(AppError::CurrentDir)?;
+    config::find_local_project(&current_dir).ok_or(AppError::LocalProjectNotFound(current_dir))
+}
+
+/// Returns the canonical path of a project file, used as its key in the
+/// trust store.
+fn canonicalize_project_file(path: &Path) -> Result<PathBuf, AppError> {
+    path.canonicalize()
+        .map_err(|_| AppError::ProjectFileNotFound(path.to_path_buf()))
+}
+
+/// Returns the project by its name or file path, see
+/// [`ProjectSource::resolve`].
+fn load_project(project_name: &str) -> Result<ProjectConfig, AppError> {
+    ProjectSource::resolve(Some(project_name))?.load()
+}
+
+/// Same as [`load_project`], without a project name returns the local
+/// project, or the picked one, see [`ProjectSource::resolve_or_pick`].
+fn load_project_or_local(project_name: Option<&str>) -> Result<ProjectConfig, AppError> {
+    ProjectSource::resolve_or_pick(project_name)?.load()
+}
+
+/// Same as [`load_project_or_local`], checking the project file against the
+/// trust store, see [`ProjectSource::load_trusted`].
+fn load_project_trusted(
+    project_name: Option<&str>,
+    trust: bool,
+) -> Result<ProjectConfig, AppError> {
+    ProjectSource::resolve_or_pick(project_name)?.load_trusted(trust)
+}
+
+/// Same as [`load_project_trusted`], recording the use of the project file,
+/// see [`ProjectSource::load_to_run`].
+fn load_project_to_run(project_name: Option<&str>, trust: bool) -> Result<ProjectConfig, AppError> {
+    ProjectSource::resolve_or_pick(project_name)?.load_to_run(trust)
+}
+
+/// Options for running a project, overriding the project's settings.
+pub struct RunOptions<'a> {
+    /// Attach to the session, `None` uses the project's `attach` setting.
+    pub attach: Option<bool>,
+    pub read_only: bool,
+    pub detach_others: bool,
+    /// `<window>[.<pane>]` targets to wait for.
+    pub wait: &'a [String],
+    /// Skip checking the project file against the trust store.
+    pub trust: bool,
+}
+
+impl<'a> From<&'a RunArgs> for RunOptions<'a> {
+    fn from(args: &'a RunArgs) -> Self {
+        RunOptions {
+            attach: match (args.attach, args.no_attach) {
+                (true, _) => Some(true),
+                (_, true) => Some(false),
+                _ => None,
+            },
+            read_only: args.read_only,
+            detach_others: args.detach_others,
+            wait: &args.wait,
+            trust: args.trust,
+        }
+    }
+}
 
-    let entries = config::get_project_yaml(project_name)?;
-    let project = ProjectConfig::try_from(entries)?;
-    let tmux = TmuxProject::new(&project)?;
+/// Parses the project file (or the local one without a project name), runs
+/// the commands to create the tmux session.
+pub fn run_project(project_name: Option<&str>, options: &RunOptions) -> Result<(), AppError> {
+    let project = load_project_to_run(project_name, options.trust)?;
+    println!("Starting project {}", project.project_name);
+    run(&project, options)
+}
+
+/// Runs the project of a directory, see [`ProjectSource::directory`].
+pub fn open_directory(dir: &str, options: &RunOptions) -> Result<(), AppError> {
+    let project = ProjectSource::directory(Path::new(&*shellexpand::full(dir)?))?
+        .load_to_run(options.trust)?;
+    println!("Starting project {}", project.project_name);
+    run(&project, options)
+}
+
+/// Runs the commands to create the project's tmux session.
+fn run(project: &ProjectConfig, options: &RunOptions) -> Result<(), AppError> {
+    for warning in project.deprecations() {
+        eprintln!("{}", warning.yellow());
+    }
+    let attach = AttachOptions {
+        attach: options.attach.or(project.attach).unwrap_or(true),
+        read_only: options.read_only,
+        detach_others: options.detach_others,
+        client: None,
+    };
+    let tmux = TmuxProject::new(project)
+        .with_attach(attach)
+        .with_wait(options.wait)?;
     Ok(tmux.run()?)
 }
 
@@ -75,7 +630,7 @@ fn bool_to_yesno(val: bool) -> ColoredString {
 /// - `$SHELL` is set.
 /// - `$EDITOR` are set.
 pub(crate) fn check_config() -> Result<(), AppError> {
-    let have_tmux = which(tmux::TMUX_BIN).is_ok();
+    let have_tmux = which(TmuxServer::get().binary()).is_ok();
     let have_editor = env::var("EDITOR").is_ok();
     let have_shell = env::var("SHELL").is_ok();
 
@@ -85,43 +640,200 @@ pub(crate) fn check_config() -> Result<(), AppError> {
         bool_to_yesno(have_editor),
         bool_to_yesno(have_shell)
     );
-
-    Ok(())
+    print_settings()
 }
 
-/// Opens an existing project file with `$EDITOR`.
-pub(crate) fn edit_project(project_name: &str) -> Result<(), AppError> {
-    let project_file_path = config::get_project_path(project_name)?;
-    if !Path::new(&project_file_path).exists() {
-        return Err(AppError::ProjectFileNotFound(project_file_path));
-    }
+/// Prints the effective global settings, see [`Settings`].
+fn print_settings() -> Result<(), AppError> {
+    let settings = Settings::get();
+    let defaults = &settings.defaults;
+    let settings_path = config::get_path(SETTINGS_FILE)?;
+    let unset = || "-".dimmed().to_string();
 
-    let editor = env::var("EDITOR");
-    if editor.is_err() {
-        return Err(AppError::EditorNotSet(project_file_path));
+    println!("\n{}", "Settings".bold());
+    let mut rows = vec![
+        (
+            "settings file",
+            if settings_path.exists() {
+                settings_path.display().to_string()
+            } else {
+                format!("{} {}", settings_path.display(), "(not found)".dimmed())
+            },
+        ),
+        ("tmux binary", settings.tmux.binary().to_string()),
+        (
+            "tmux socket",
+            settings.tmux.socket.clone().unwrap_or_else(unset),
+        ),
+        (
+            "editor",
+            settings
+                .editor()
+                .map(|(editor, source)| format!("{editor} {}", format!("({source})").dimmed()))
+                .unwrap_or_else(unset),
+        ),
+        (
+            "template dir",
+            settings.template_dir()?.display().to_string(),
+        ),
+        (
+            "layout",
+            defaults
+                .layout
+                .as_deref()
+                .unwrap_or(DEFAULT_LAYOUT)
+                .to_string(),
+        ),
+        (
+            "pre_window",
+            defaults
+                .pre_window
+                .as_ref()
+                .map(|commands| commands.join("; "))
+                .unwrap_or_else(unset),
+        ),
+        (
+            "attach",
+            bool_to_yesno(defaults.attach.unwrap_or(true)).to_string(),
+        ),
+        (
+            "env",
+            if defaults.env.is_empty() {
+                unset()
+            } else {
+                defaults
+                    .env
+                    .iter()
+                    .map(|(name, value)| format!("{name}={value}"))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            },
+        ),
+    ];
+    for (i, dir) in config::search_path()?.iter().enumerate() {
+        let label = if i == 0 { "search path" } else { "" };
+        rows.push((label, dir.display().to_string()));
     }
+    for (label, value) in rows {
+        println!("{label:<16}{value}");
+    }
+    Ok(())
+}
 
-    let mut binding = Command::new(editor.unwrap());
-    let cmd = binding.arg(project_file_path);
+/// Opens the file with the editor, see [`Settings::editor`]. Editors with
+/// arguments (e.g. `code --wait`) are split like the shell does.
+fn open_in_editor(path: &Path) -> Result<(), AppError> {
+    let (editor, _) = Settings::get()
+        .editor()
+        .ok_or_else(|| AppError::EditorNotSet(path.to_path_buf()))?;
+    let mut parts = shlex::split(&editor).unwrap_or_default().into_iter();
+    let program = parts
+        .next()
+        .ok_or_else(|| AppError::CommandRun(editor.clone()))?;
+
+    let mut binding = Command::new(program);
+    let cmd = binding.args(parts).arg(path);
 
     cmd.status()
         .map_err(|_| AppError::CommandRun(format!("{cmd:?}")))?;
     Ok(())
 }
 
+/// Checks the project file is valid, the error has the location of the
+/// problem if not.
+fn validate_project_file(path: &Path) -> Result<(), AppError> {
+    let contents = config::read_project_file(path)?;
+    config::parse_project_yaml(path, &contents)?;
+    ProjectConfig::try_from(contents)
+        .map_err(|e| AppError::ProjectFileInvalid(path.to_path_buf(), e))?;
+    Ok(())
+}
+
+/// Opens the project file with the editor, and validates it once the editor
+/// exits. The file's content is kept in a backup file (next to it, with a
+/// `.bak` extension) meanwhile, to revert an invalid file to.
+fn edit_project_file(path: &Path) -> Result<(), AppError> {
+    let mut backup_name = path.file_name().unwrap_or_default().to_os_string();
+    backup_name.push(".bak");
+    let backup_path = path.with_file_name(backup_name);
+    copy(path, &backup_path)
+        .map_err(|e| AppError::ProjectCopy(path.to_path_buf(), backup_path.clone(), e))?;
+
+    let result = edit_until_valid(path, &backup_path);
+    let _ = remove_file(&backup_path);
+    result
+}
+
+/// Opens the project file with the editor until it's valid. On a terminal,
+/// an invalid file can be re-opened, reverted to the backup, or kept anyway.
+/// Otherwise the error is returned, keeping the file.
+fn edit_until_valid(path: &Path, backup_path: &Path) -> Result<(), AppError> {
+    loop {
+        open_in_editor(path)?;
+        let Err(e) = validate_project_file(path) else {
+            return Ok(());
+        };
+        if !io::stdin().is_terminal() {
+            return Err(e);
+        }
+
+        eprintln!("{}", e.to_string().red());
+        let choice = Select::new()
+            .with_prompt("What to do with the invalid file?")
+            .items(["Re-open the editor", "Revert the changes", "Keep it anyway"])
+            .default(0)
+            .interact()
+            .map_err(AppError::Prompt)?;
+        match choice {
+            0 => continue,
+            1 => {
+                copy(backup_path, path).map_err(|e| {
+                    AppError::ProjectCopy(backup_path.to_path_buf(), path.to_path_buf(), e)
+                })?;
+                return Ok(());
+            }
+            _ => return Ok(()),
+        }
+    }
+}
+
+/// Opens an existing project file with the editor, see [`edit_project_file`].
+pub(crate) fn edit_project(project_name: Option<&str>) -> Result<(), AppError> {
+    let project_file_path = match project_name {
+        Some(project_name) => config::get_project_path(project_name)?,
+        None => match get_local_project_path() {
+            Err(AppError::LocalProjectNotFound(_)) if can_prompt() => pick_project()?,
+            result => result?,
+        },
+    };
+    if !Path::new(&project_file_path).exists() {
+        return Err(AppError::ProjectFileNotFound(project_file_path));
+    }
+
+    History::record(&project_file_path);
+    edit_project_file(&project_file_path)
+}
+
 /// Create the new project file from a blank template (only containing) name, or a
-/// pre-defined template.
+/// template, see [`Template`].
 pub enum NewProjectFrom<'a> {
-    DefaultTemplate { name: &'a str },
-    Blank { name: &'a str },
+    Template {
+        name: &'a str,
+        template: &'a str,
+        /// Values of the template's variables, the others are asked for.
+        vars: &'a [(String, String)],
+    },
+    Blank {
+        name: &'a str,
+    },
 }
 
-/// Creates a new project file, optinally from [`default_template`], and
-/// opens it with `$EDITOR`.
+/// Creates a new project file, optinally from a template, and opens it with
+/// the editor, see [`edit_project_file`].
 pub(crate) fn new_project(project_from: &NewProjectFrom) -> Result<(), AppError> {
     let project_name = match project_from {
         NewProjectFrom::Blank { name } => name,
-        NewProjectFrom::DefaultTemplate { name } => name,
+        NewProjectFrom::Template { name, .. } => name,
     };
 
     let project_file_path = config::get_project_path(project_name)?;
@@ -138,34 +850,91 @@ pub(crate) fn new_project(project_from: &NewProjectFrom) -> Result<(), AppError>
         .to_string_lossy();
     let content = match project_from {
         NewProjectFrom::Blank { name: _ } => format!("project_name: {project_name}"),
-        NewProjectFrom::DefaultTemplate { name: _ } => format!(default_template!(), project_name),
+        NewProjectFrom::Template { template, vars, .. } => {
+            let template = Template::load(template)?;
+            let values = template.values(&project_name, vars, io::stdin().is_terminal())?;
+            template.render(&values)?
+        }
     };
 
+    config::create_project_dir(&project_file_path)?;
     let mut new_file = File::create(&project_file_path)
         .map_err(|e| AppError::ProjectFileCreate(project_file_path.clone(), e))?;
     new_file
         .write_all(content.as_bytes())
         .map_err(|e| AppError::ProjectFileWrite(project_file_path.clone(), e))?;
 
-    let editor = env::var("EDITOR");
-    if editor.is_err() {
-        return Err(AppError::EditorNotSet(project_file_path));
-    }
+    edit_project_file(&project_file_path)
+}
 
-    let mut binding = Command::new(editor.unwrap());
-    let cmd = binding.arg(project_file_path);
+/// Lists the templates. On a terminal shows their variables as well.
+pub(crate) fn list_templates() -> Result<(), AppError> {
+    let names = Template::names()?;
+    if !io::stdout().is_terminal() {
+        names.iter().for_each(|name| println!("{name}"));
+        return Ok(());
+    }
+    for name in names {
+        match Template::load(&name) {
+            Ok(template) => {
+                let mut details: Vec<_> =
+                    template.variables.iter().map(|v| v.name.as_str()).collect();
+                if template.path.is_none() {
+                    details.push("built-in");
+                }
+                if details.is_empty() {
+                    println!("{name}");
+                } else {
+                    println!("{name} {}", format!("({})", details.join(", ")).dimmed());
+                }
+            }
+            Err(e) => println!("{name} {}", e.to_string().red()),
+        }
+    }
+    Ok(())
+}
 
-    cmd.status()
-        .map_err(|_| AppError::CommandRun(format!("{cmd:?}")))?;
+/// Prints a template's file, or the built-in default template.
+pub(crate) fn show_template(name: &str) -> Result<(), AppError> {
+    let template = Template::load(name)?;
+    match &template.path {
+        Some(path) => {
+            let content =
+                read_to_string(path).map_err(|e| AppError::TemplateFileRead(path.clone(), e))?;
+            print!("{content}");
+        }
+        None => print!("{}", template.body),
+    }
     Ok(())
 }
 
+/// Opens a template with the editor. A missing template is created from the
+/// built-in one first.
+pub(crate) fn edit_template(name: &str) -> Result<(), AppError> {
+    let path = Template::path(name)?;
+    if !path.exists() {
+        if let Some(dir) = path.parent() {
+            create_dir_all(dir).map_err(|e| AppError::TemplateFileWrite(dir.to_path_buf(), e))?;
+        }
+        write(&path, Template::new_file_content())
+            .map_err(|e| AppError::TemplateFileWrite(path.clone(), e))?;
+    }
+    open_in_editor(&path)
+}
+
 /// Deletes a project from the configuration directory. Asks for confirmation.
-pub(crate) fn delete_project(project_name: &str) -> Result<(), AppError> {
-    let project_file_path = config::get_project_path(project_name)?;
+pub(crate) fn delete_project(project_name: Option<&str>) -> Result<(), AppError> {
+    let project_file_path = match project_name {
+        Some(project_name) => config::get_project_path(project_name)?,
+        None => pick_project()?,
+    };
     if !project_file_path.exists() {
         return Err(AppError::ProjectFileNotFound(project_file_path));
     }
+    let project_name = match project_name {
+        Some(project_name) => project_name.to_string(),
+        None => project_file_path.display().to_string(),
+    };
     let message = format!("Are you sure you want to delete \"{project_name}\"?");
     let confirmation = Confirm::new()
         .with_prompt(message)
@@ -173,7 +942,8 @@ pub(crate) fn delete_project(project_name: &str) -> Result<(), AppError> {
         .map_err(AppError::Prompt)?;
     if confirmation {
         remove_file(&project_file_path)
-            .map_err(|e| AppError::ProjectFileDelete(project_file_path, e))?;
+            .map_err(|e| AppError::ProjectFileDelete(project_file_path.clone(), e))?;
+        config::remove_empty_project_dirs(&project_file_path)?;
         println!("Deleted \"{project_name}\"");
     } else {
         println!("Delete aborted");
@@ -182,8 +952,12 @@ pub(crate) fn delete_project(project_name: &str) -> Result<(), AppError> {
     Ok(())
 }
 
-/// Copies an existing project to a new one, and opens it with `$EDITOR`.
-pub(crate) fn copy_project(existing: &str, new: &str) -> Result<(), AppError> {
+/// Returns the paths of an existing project and a new one, and the new
+/// project's name, the base name of `new` (which can be a path).
+fn get_new_project_paths(
+    existing: &str,
+    new: &str,
+) -> Result<(PathBuf, PathBuf, String), AppError> {
     let existing_path = config::get_project_path(existing)?;
     if !existing_path.exists() {
         return Err(AppError::ProjectFileNotFound(existing_path));
@@ -194,25 +968,553 @@ pub(crate) fn copy_project(existing: &str, new: &str) -> Result<(), AppError> {
         return Err(AppError::ProjectFileExists(new_path));
     }
 
-    copy(&existing_path, &new_path)
-        .map_err(|e| AppError::ProjectCopy(existing_path, new_path.clone(), e))?;
-    let editor = env::var("EDITOR");
-    if editor.is_err() {
-        return Err(AppError::EditorNotSet(new_path));
+    let new_name = Path::new(new)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .ok_or(AppError::GetProjectNameFromFilePath(new.to_string()))?;
+    Ok((existing_path, new_path, new_name))
+}
+
+/// Writes the existing project to the new path, with the new name and
+/// optionally a new root, see [`ProjectRewrite`].
+fn write_rewritten_project(
+    existing_path: &Path,
+    new_path: &Path,
+    rewrite: &ProjectRewrite,
+) -> Result<(), AppError> {
+    let contents = config::read_project_file(existing_path)?;
+    let rewritten = rewrite.apply(&contents)?;
+    config::create_project_dir(new_path)?;
+    write(new_path, rewritten).map_err(|e| AppError::ProjectFileWrite(new_path.to_path_buf(), e))
+}
+
+/// Copies an existing project to a new one, named after it and optionally
+/// with a new root, and opens it with the editor, see [`edit_project_file`].
+pub(crate) fn copy_project(existing: &str, new: &str, root: Option<&str>) -> Result<(), AppError> {
+    let (existing_path, new_path, new_name) = get_new_project_paths(existing, new)?;
+    let rewrite = ProjectRewrite {
+        name: &new_name,
+        root,
+    };
+    write_rewritten_project(&existing_path, &new_path, &rewrite)?;
+    edit_project_file(&new_path)
+}
+
+/// Renames a project, its file and name (and optionally its root), and its
+/// session if running.
+pub(crate) fn rename_project(
+    existing: &str,
+    new: &str,
+    root: Option<&str>,
+) -> Result<(), AppError> {
+    let (existing_path, new_path, new_name) = get_new_project_paths(existing, new)?;
+    let project = ProjectConfig::try_from(config::read_project_file(&existing_path)?)?;
+    let rewrite = ProjectRewrite {
+        name: &new_name,
+        root,
+    };
+    write_rewritten_project(&existing_path, &new_path, &rewrite)?;
+    remove_file(&existing_path)
+        .map_err(|e| AppError::ProjectFileDelete(existing_path.clone(), e))?;
+    config::remove_empty_project_dirs(&existing_path)?;
+    println!("Renamed \"{existing}\" to \"{new}\"");
+
+    if TmuxProject::new(&project).rename(&new_name)? {
+        println!(
+            "Renamed session \"{}\" to \"{new_name}\"",
+            project.project_name
+        );
     }
+    Ok(())
+}
 
-    let mut binding = Command::new(editor.unwrap());
-    let cmd = binding.arg(&new_path);
+/// Converts another session manager's config file to a new project file.
+pub(crate) fn import_project(
+    format: ImportFormat,
+    file: &str,
+    name: Option<&str>,
+) -> Result<(), AppError> {
+    let file_path = PathBuf::from(shellexpand::full(file)?.as_ref());
+    let mut contents = String::new();
+    File::open(&file_path)
+        .map_err(|_| AppError::ProjectFileNotFound(file_path.clone()))?
+        .read_to_string(&mut contents)
+        .map_err(|e| AppError::ProjectFileRead(file_path.clone(), e))?;
 
-    cmd.status()
-        .map_err(|_| AppError::CommandRun(format!("{cmd:?}")))?;
+    // name can be a path, so extract the base name from the path as the project's name
+    let name_from = |path: &str| {
+        Path::new(path)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .ok_or(AppError::GetProjectNameFromFilePath(path.to_string()))
+    };
+    let default_name = name_from(file)?;
+    let imported = match format {
+        ImportFormat::Tmuxp => tmuxp::import(&contents, &default_name),
+        ImportFormat::Teamocil => teamocil::import(&contents, &default_name),
+        ImportFormat::Smug => smug::import(&contents, &default_name),
+    }
+    .map_err(|e| AppError::Import(file_path.clone(), e))?;
+    for warning in &imported.warnings {
+        eprintln!("{}", warning.yellow());
+    }
+
+    let mut project = imported.project;
+    if let Some(name) = name {
+        project.project_name = name_from(name)?;
+    }
+    let project_file_path = config::get_project_path(name.unwrap_or(&project.project_name))?;
+    if project_file_path.exists() {
+        return Err(AppError::ProjectFileExists(project_file_path));
+    }
+
+    let content = project.to_yaml().map_err(AppError::ProjectSerialize)?;
+    config::create_project_dir(&project_file_path)?;
+    let mut new_file = File::create(&project_file_path)
+        .map_err(|e| AppError::ProjectFileCreate(project_file_path.clone(), e))?;
+    new_file
+        .write_all(content.as_bytes())
+        .map_err(|e| AppError::ProjectFileWrite(project_file_path.clone(), e))?;
+    println!(
+        "Imported {} to {}",
+        file_path.display(),
+        project_file_path.display()
+    );
+    Ok(())
+}
+
+/// Prints the project converted to another session manager's format.
+pub(crate) fn export_project(project_name: &str, format: ExportFormat) -> Result<(), AppError> {
+    let project = load_project(project_name)?;
+    let exported = match format {
+        ExportFormat::Tmuxp => tmuxp::export(&project),
+        ExportFormat::Zellij => zellij::export(&project),
+    }
+    .map_err(|e| AppError::Export(project_name.to_string(), e))?;
+    for warning in &exported.warnings {
+        eprintln!("{}", warning.yellow());
+    }
+    print!("{}", exported.content);
+    Ok(())
+}
+
+/// Sets the trust status of a project file outside the configuration
+/// directory (or the local project file without a project name).
+pub(crate) fn set_trust(project_name: Option<&str>, status: TrustStatus) -> Result<(), AppError> {
+    let source = ProjectSource::resolve(project_name)?;
+    let Some(path) = source.untrusted_file() else {
+        let (ProjectSource::File(path)
+        | ProjectSource::Local(path)
+        | ProjectSource::Directory(path)) = source;
+        return Err(AppError::NothingToTrust(path));
+    };
+    let path = canonicalize_project_file(path)?;
+    let contents = config::read_project_file(&path)?;
+    let mut store = TrustStore::load()?;
+    store.set(&path, contents.as_bytes(), status);
+    store.save()?;
+    match status {
+        TrustStatus::Allowed => println!("Allowed {}", path.display()),
+        TrustStatus::Denied => println!("Denied {}", path.display()),
+    }
+    Ok(())
+}
+
+/// Lists the project files in the trust store, noting the ones changed since
+/// allowed or removed.
+pub(crate) fn list_trusted() -> Result<(), AppError> {
+    let store = TrustStore::load()?;
+    for (path, entry) in store.entries() {
+        let status = match entry.status {
+            TrustStatus::Allowed => "allowed",
+            TrustStatus::Denied => "denied",
+        };
+        let note = match config::read_project_file(path) {
+            Err(_) => " (missing)",
+            Ok(contents) if store.check(path, contents.as_bytes()) == Trust::Changed => {
+                " (changed)"
+            }
+            Ok(_) => "",
+        };
+        println!("{status:<8}{}{}", path.display(), note.dimmed());
+    }
     Ok(())
 }
 
 /// Kills the project's session.
-pub(crate) fn stop(project_name: &str) -> Result<(), AppError> {
-    let entries = config::get_project_yaml(project_name)?;
-    let project = ProjectConfig::try_from(entries)?;
-    let tmux = TmuxProject::new(&project)?;
+pub(crate) fn stop(project_name: Option<&str>, trust: bool) -> Result<(), AppError> {
+    let project = load_project_trusted(project_name, trust)?;
+    let tmux = TmuxProject::new(&project);
     Ok(tmux.stop()?)
 }
+
+/// Runs the action for each of the named projects. A failing project is
+/// reported and the others are still run, unless `fail_fast` is set, in
+/// which case its error is returned right away. Fails with
+/// [`AppError::ProjectsFailed`] if any of them failed.
+fn for_each_project<T>(
+    projects: Vec<(String, T)>,
+    fail_fast: bool,
+    mut action: impl FnMut(T) -> Result<(), AppError>,
+) -> Result<(), AppError> {
+    let total = projects.len();
+    let mut failed = 0;
+    for (name, project) in projects {
+        if let Err(error) = action(project) {
+            if fail_fast {
+                return Err(error);
+            }
+            eprintln!("{}", format!("Project {name} failed: {error}").red());
+            failed += 1;
+        }
+    }
+    match failed {
+        0 => Ok(()),
+        _ => Err(AppError::ProjectsFailed(failed, total)),
+    }
+}
+
+/// Runs the projects with all the tags, detached, see
+/// [`load_tagged_projects`] and [`for_each_project`].
+pub(crate) fn run_tagged(
+    tags: &[String],
+    options: &RunOptions,
+    fail_fast: bool,
+) -> Result<(), AppError> {
+    let options = RunOptions {
+        attach: Some(false),
+        wait: &[],
+        ..*options
+    };
+    let projects = load_tagged_projects(tags)?
+        .into_iter()
+        .map(|project| (project.name.clone(), project))
+        .collect();
+    for_each_project(projects, fail_fast, |project| {
+        let project = project.source().load_to_run(options.trust)?;
+        println!("Starting project {}", project.project_name);
+        run(&project, &options)
+    })
+}
+
+/// Stops the running sessions of the projects with all the tags, see
+/// [`load_tagged_projects`] and [`for_each_project`].
+pub(crate) fn stop_tagged(tags: &[String], trust: bool, fail_fast: bool) -> Result<(), AppError> {
+    let running = TmuxServer::get().session_names();
+    let projects = load_tagged_projects(tags)?
+        .into_iter()
+        .filter(|project| {
+            project
+                .config
+                .as_ref()
+                .is_some_and(|config| running.contains(&config.project_name))
+        })
+        .map(|project| (project.name.clone(), project))
+        .collect();
+    for_each_project(projects, fail_fast, |project| {
+        let project = project.source().load_trusted(trust)?;
+        println!("Stopping project {}", project.project_name);
+        Ok(TmuxProject::new(&project).stop()?)
+    })
+}
+
+/// Runs the projects of a group in order, detached, see
+/// [`for_each_project`].
+///
+/// The group's `on_start` hook runs first. Once the projects are started,
+/// attaches to the group's `attach` project (if set), or with `--attach` to
+/// its first project.
+pub(crate) fn run_group(
+    group_name: &str,
+    options: &RunOptions,
+    fail_fast: bool,
+) -> Result<(), AppError> {
+    let group = Group::get(group_name)?;
+    if !options.wait.is_empty() {
+        return Err(AppError::GroupWait(group_name.to_string()));
+    }
+    let attach_to = match (options.attach, &group.attach) {
+        (Some(false), _) => None,
+        (_, Some(attach)) => Some(
+            group
+                .projects
+                .iter()
+                .find(|member| member.project() == attach)
+                .ok_or_else(|| AppError::GroupAttach(group_name.to_string(), attach.clone()))?,
+        ),
+        (Some(true), None) => group.projects.first(),
+        (None, None) => None,
+    };
+    // Check the arguments of all the projects before starting any of them
+    let members = group
+        .projects
+        .iter()
+        .map(|member| {
+            Ok((
+                member.project().to_string(),
+                (member, member.run_args(group_name)?),
+            ))
+        })
+        .collect::<Result<Vec<_>, AppError>>()?;
+
+    run_hook("on_start", &group.on_start)?;
+    let mut started = vec![];
+    let result = for_each_project(members, fail_fast, |(member, args)| {
+        let member_options = RunOptions {
+            attach: Some(false),
+            trust: options.trust || args.trust,
+            ..RunOptions::from(&args)
+        };
+        let project =
+            ProjectSource::resolve(Some(member.project()))?.load_to_run(member_options.trust)?;
+        println!("Starting project {}", project.project_name);
+        run(&project, &member_options)?;
+        started.push((member, project));
+        Ok(())
+    });
+    if fail_fast && result.is_err() {
+        return result;
+    }
+
+    let attach_project = attach_to.and_then(|attach_to| {
+        started
+            .iter()
+            .find(|(member, _)| *member == attach_to)
+            .map(|(_, project)| project)
+    });
+    if let Some(project) = attach_project {
+        let attach = AttachOptions {
+            attach: true,
+            read_only: options.read_only,
+            detach_others: options.detach_others,
+            client: None,
+        };
+        TmuxProject::new(project).with_attach(attach).attach()?;
+    }
+    result
+}
+
+/// Stops the running sessions of a group's projects, in the reverse order
+/// they're started, see [`for_each_project`]. Then runs the group's
+/// `on_stop` hook.
+pub(crate) fn stop_group(group_name: &str, trust: bool, fail_fast: bool) -> Result<(), AppError> {
+    let group = Group::get(group_name)?;
+    let running = TmuxServer::get().session_names();
+    let members = group
+        .projects
+        .iter()
+        .rev()
+        .map(|member| (member.project().to_string(), member))
+        .collect();
+    let result = for_each_project(members, fail_fast, |member| {
+        let trust = trust || member.run_args(group_name)?.trust;
+        let project = ProjectSource::resolve(Some(member.project()))?.load_trusted(trust)?;
+        if running.contains(&project.project_name) {
+            println!("Stopping project {}", project.project_name);
+            TmuxProject::new(&project).stop()?;
+        }
+        Ok(())
+    });
+    if fail_fast && result.is_err() {
+        return result;
+    }
+    run_hook("on_stop", &group.on_stop)?;
+    result
+}
+
+/// Prints whether the project's session is running, or the sessions of a
+/// group's projects.
+pub(crate) fn project_status(project_name: Option<&str>) -> Result<(), AppError> {
+    let running = TmuxServer::get().session_names();
+    let status = |project: &ProjectConfig| match running.contains(&project.project_name) {
+        true => "running".green(),
+        false => "stopped".dimmed(),
+    };
+    let print_views = |project: &ProjectConfig| {
+        for view in view_names(&project.project_name, &running) {
+            println!("  {} {view}", "view".dimmed());
+        }
+    };
+    let Some(group_name) = project_name.and_then(group_name) else {
+        let project = load_project_or_local(project_name)?;
+        println!("{}  {}", project.project_name, status(&project));
+        print_views(&project);
+        return Ok(());
+    };
+
+    let members = &Group::get(group_name)?.projects;
+    let width = members
+        .iter()
+        .map(|member| member.project().chars().count())
+        .max()
+        .unwrap_or(0);
+    for member in members {
+        match load_project(member.project()) {
+            Ok(project) => {
+                println!("{:width$}  {}", member.project(), status(&project));
+                print_views(&project);
+            }
+            Err(error) => {
+                let status = format!("invalid: {error}").red();
+                println!("{:width$}  {status}", member.project());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Options of the `attach` command.
+pub struct AttachProjectOptions<'a> {
+    /// Attach to a new view of the session, see [`TmuxProject::attach_view`].
+    pub view: bool,
+    /// The window (name or index) to select.
+    pub window: Option<&'a str>,
+    /// The pane of the window to select.
+    pub pane: Option<usize>,
+    /// Switch this client to the session, see [`AttachOptions::client`].
+    pub client: Option<&'a str>,
+    pub read_only: bool,
+    pub detach_others: bool,
+}
+
+/// Attaches (or switches) to the project's running session, optionally
+/// selecting one of its windows, and a pane of it. With `view` attaches to a
+/// new view of the session instead.
+pub(crate) fn attach_project(
+    project_name: Option<&str>,
+    options: &AttachProjectOptions,
+) -> Result<(), AppError> {
+    let project = load_project_or_local(project_name)?;
+    let attach = AttachOptions {
+        attach: true,
+        read_only: options.read_only,
+        detach_others: options.detach_others,
+        client: options.client.map(String::from),
+    };
+    let tmux = TmuxProject::new(&project).with_attach(attach);
+    if options.view {
+        return Ok(tmux.attach_view(options.window, options.pane)?);
+    }
+    if let Some(window) = options.window {
+        tmux.select_window(window, options.pane)?;
+    }
+    Ok(tmux.attach()?)
+}
+
+/// Switches the current tmux client to the session of another running
+/// project: the next (or previous) one ordered by name, wrapping around, or
+/// the one picked with a fuzzy picker. Without a terminal, the picker is shown
+/// in a popup of the client.
+pub(crate) fn switch_session(next: bool, previous: bool) -> Result<(), AppError> {
+    let server = TmuxServer::get();
+    let current = server.current_session().ok_or(AppError::NotInsideTmux)?;
+    let running = server.session_names();
+    let mut projects: Vec<_> = load_projects(true)?
+        .into_iter()
+        .filter_map(|project| project.config)
+        .filter(|config| running.contains(&config.project_name))
+        .collect();
+    projects.sort_by(|a, b| a.project_name.cmp(&b.project_name));
+    projects.dedup_by(|a, b| a.project_name == b.project_name);
+    projects.retain(|config| config.project_name != current);
+    if projects.is_empty() {
+        return Err(AppError::NoOtherRunningProjects);
+    }
+
+    let index = if next || previous {
+        // The first one after the current session, or the last one before it
+        let after = projects.partition_point(|config| config.project_name < current);
+        match (next, after) {
+            (true, after) if after < projects.len() => after,
+            (true, _) => 0,
+            (false, 0) => projects.len() - 1,
+            (false, after) => after - 1,
+        }
+    } else if !can_prompt() {
+        // E.g. from `run-shell` in a key binding, pick in a popup instead
+        return Ok(server.rerun_in_popup()?);
+    } else {
+        let items: Vec<_> = projects
+            .iter()
+            .map(|config| config.project_name.as_str())
+            .collect();
+        FuzzySelect::new()
+            .with_prompt("Session")
+            .items(&items)
+            .default(0)
+            .interact_opt()
+            .map_err(AppError::Prompt)?
+            .ok_or(AppError::NoProjectSelected)?
+    };
+    let tmux = TmuxProject::new(&projects[index]).with_attach(AttachOptions::default());
+    Ok(tmux.attach()?)
+}
+
+/// Restarts the project's session, or just one of its windows.
+pub(crate) fn restart_project(
+    project_name: &str,
+    window: Option<&str>,
+    trust: bool,
+) -> Result<(), AppError> {
+    let project = load_project_trusted(Some(project_name), trust)?;
+    let tmux = TmuxProject::new(&project);
+    match window {
+        Some(window) => Ok(tmux.restart_window(window)?),
+        None => Ok(tmux.restart()?),
+    }
+}
+
+/// Helper mapping the CLI's [`SendArgs`] to [`SendOptions`].
+fn get_send_options(send: &SendArgs) -> Result<SendOptions, AppError> {
+    let wait_for_output = send
+        .wait_for_output
+        .as_ref()
+        .map(|re| Regex::new(re).map_err(|e| AppError::InvalidRegex(re.clone(), e)))
+        .transpose()?;
+
+    Ok(SendOptions {
+        literal: send.literal,
+        enter: !send.no_enter,
+        wait_for_output,
+        timeout: Duration::from_secs(send.timeout),
+    })
+}
+
+/// Sends keys to a pane of the project's running session.
+pub(crate) fn send_keys(
+    project_name: &str,
+    target: &str,
+    keys: &[String],
+    send: &SendArgs,
+) -> Result<(), AppError> {
+    let options = get_send_options(send)?;
+    let project = load_project(project_name)?;
+    let tmux = TmuxProject::new(&project);
+    Ok(tmux.send_keys(target, keys, &options)?)
+}
+
+/// Runs a command in all the panes of the project's running session, or the
+/// panes of one of its windows.
+pub(crate) fn exec_command(
+    project_name: &str,
+    window: Option<&str>,
+    command: &str,
+    send: &SendArgs,
+) -> Result<(), AppError> {
+    let options = get_send_options(send)?;
+    let project = load_project(project_name)?;
+    let tmux = TmuxProject::new(&project);
+    Ok(tmux.exec(window, command, &options)?)
+}
+
+/// Prints the output of a pane of the project's running session.
+pub(crate) fn pane_logs(
+    project_name: &str,
+    target: &str,
+    lines: usize,
+    follow: bool,
+) -> Result<(), AppError> {
+    let project = load_project(project_name)?;
+    let tmux = TmuxProject::new(&project);
+    Ok(tmux.logs(target, lines, follow)?)
+}
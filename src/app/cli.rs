@@ -1,9 +1,14 @@
 //! CLI arguments parser.
+use crate::tmux::TmuxVersion;
 use clap::command;
+use std::path::PathBuf;
 
 #[derive(Debug, Parser)]
 #[command(version, about, arg_required_else_help = true)]
 pub struct Cli {
+    /// Search this directory for projects first, and create new ones in it
+    #[arg(long, global = true, value_name = "DIR")]
+    pub config_dir: Option<PathBuf>,
     #[clap(subcommand)]
     pub commands: Commands,
 }
@@ -11,34 +16,173 @@ pub struct Cli {
 #[derive(Debug, Subcommand)]
 pub enum Commands {
     /// Run the project's commands
-    #[command(visible_alias = "start", arg_required_else_help = true)]
+    #[command(visible_alias = "start")]
     Run {
+        /// Project name or filesystem path (a directory path, e.g. `.`, runs
+        /// the directory's project), or `@group` to run the group's
+        /// projects. Defaults to the local project file, or the one picked
+        /// from the projects in a terminal
+        project: Option<String>,
+        /// Run the projects with the tag instead, detached. Can be repeated
+        /// to require several tags
+        #[arg(long, conflicts_with_all = ["project", "attach", "wait"])]
+        tag: Vec<String>,
+        /// With a group or tags, stop at the first project failing to start
+        /// instead of reporting it and going on
+        #[arg(long)]
+        fail_fast: bool,
+        #[command(flatten)]
+        run: RunArgs,
+    },
+    /// Run a session for a directory, from its local project file if exists
+    /// or from the directory template
+    Open {
+        /// The directory
+        #[arg(default_value = ".")]
+        dir: String,
+        #[command(flatten)]
+        run: RunArgs,
+    },
+    /// Restart the project's session, or a single window of it
+    #[command(arg_required_else_help = true)]
+    Restart {
         /// Project name or filesystem path
         project: String,
+        /// Re-create only this window, leaving the others untouched
+        #[arg(long)]
+        window: Option<String>,
+        /// Don't check the project file against the trust store, e.g. in CI
+        #[arg(long)]
+        trust: bool,
     },
-    /// Stop the project's session
-    #[command(visible_alias = "kill", arg_required_else_help = true)]
-    Stop {
+    /// Send keys to a pane of a running project
+    #[command(arg_required_else_help = true)]
+    Send {
         /// Project name or filesystem path
         project: String,
+        /// Window name or index, and optional pane index
+        #[arg(value_name = "WINDOW[.PANE]")]
+        target: String,
+        #[command(flatten)]
+        send: SendArgs,
+        /// Keys to send, e.g. `C-c` or `npm start`
+        #[arg(last = true, required = true)]
+        keys: Vec<String>,
     },
-    /// Output shell commands for a project
+    /// Run a command in all the panes of a running project, or of one window
     #[command(arg_required_else_help = true)]
-    Debug {
+    Exec {
         /// Project name or filesystem path
         project: String,
+        /// Run only in the panes of this window (name or index), or in a
+        /// single pane of it (`<window>.<pane>`)
+        #[arg(long)]
+        window: Option<String>,
+        #[command(flatten)]
+        send: SendArgs,
+        /// The command to run
+        #[arg(last = true, required = true)]
+        command: Vec<String>,
     },
-    /// Edit an existing project
+    /// Print the output of a pane of a running project
     #[command(arg_required_else_help = true)]
-    Edit {
+    Logs {
         /// Project name or filesystem path
         project: String,
+        /// Window name or index, and optional pane index
+        #[arg(value_name = "WINDOW[.PANE]")]
+        target: String,
+        /// Number of lines from the pane's history to print
+        #[arg(short = 'n', long, default_value_t = 100)]
+        lines: usize,
+        /// Keep printing new output
+        #[arg(short, long)]
+        follow: bool,
+    },
+    /// Stop the project's session
+    #[command(visible_alias = "kill")]
+    Stop {
+        /// Project name or filesystem path, or `@group` to stop the group's
+        /// projects. Defaults to the local project file, or the one picked
+        /// from the projects in a terminal
+        project: Option<String>,
+        /// Stop the running projects with the tag instead. Can be repeated to
+        /// require several tags
+        #[arg(long, conflicts_with = "project")]
+        tag: Vec<String>,
+        /// With a group or tags, stop at the first project failing to stop
+        /// instead of reporting it and going on
+        #[arg(long)]
+        fail_fast: bool,
+        /// Don't check the project file against the trust store, e.g. in CI
+        #[arg(long)]
+        trust: bool,
+    },
+    /// Show whether the project's session is running
+    Status {
+        /// Project name or filesystem path, or `@group` for the group's
+        /// projects. Defaults to the local project file, or the one picked
+        /// from the projects in a terminal
+        project: Option<String>,
+    },
+    /// Attach to the project's running session
+    Attach {
+        /// Project name or filesystem path. Defaults to the local project file,
+        /// or the one picked from the projects in a terminal
+        project: Option<String>,
+        /// Attach to a new view of the session, sharing its windows but with
+        /// its own current window. Destroyed once detached
+        #[arg(long)]
+        view: bool,
+        /// Select this window (name or index)
+        #[arg(long)]
+        window: Option<String>,
+        /// Select this pane of the window
+        #[arg(long, requires = "window")]
+        pane: Option<usize>,
+        /// Switch this client to the session, e.g. /dev/pts/3 (see `tmux
+        /// list-clients`), instead of attaching the terminal
+        #[arg(long)]
+        client: Option<String>,
+        /// Attach in read-only mode
+        #[arg(short, long, conflicts_with = "view")]
+        read_only: bool,
+        /// Detach other clients attached to the session
+        #[arg(short, long, conflicts_with = "view")]
+        detach_others: bool,
+    },
+    /// Switch the current tmux client to another running project's session,
+    /// picked with a fuzzy picker, e.g. from a key binding
+    Switch {
+        /// Switch to the next running project, ordered by name
+        #[arg(long, conflicts_with = "previous")]
+        next: bool,
+        /// Switch to the previous running project, ordered by name
+        #[arg(long)]
+        previous: bool,
+    },
+    /// Output shell commands for a project
+    Debug {
+        /// Project name or filesystem path. Defaults to the local project file,
+        /// or the one picked from the projects in a terminal
+        project: Option<String>,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = DebugFormat::Bash)]
+        format: DebugFormat,
+        #[command(flatten)]
+        tmux: TmuxArgs,
+    },
+    /// Edit an existing project
+    Edit {
+        /// Project name or filesystem path. Defaults to the local project file,
+        /// or the one picked from the projects in a terminal
+        project: Option<String>,
     },
     /// Delete an existing project
-    #[command(arg_required_else_help = true)]
     Delete {
-        /// Project name or filesystem path
-        project: String,
+        /// Project name or filesystem path. Picked from the projects in a
+        /// terminal
+        project: Option<String>,
     },
     /// Create a new project
     #[command(arg_required_else_help = true)]
@@ -46,12 +190,38 @@ pub enum Commands {
         /// Project name or filesystem path
         project: String,
         /// Don't use a template for the file
-        #[arg(long)]
+        #[arg(long, conflicts_with_all = ["template", "vars"])]
         blank: bool,
+        /// The template to use, from the template directory. Defaults to
+        /// the `default` template
+        #[arg(short, long)]
+        template: Option<String>,
+        /// Set a variable of the template instead of asking for it. Can be
+        /// repeated
+        #[arg(long = "var", value_name = "NAME=VALUE", value_parser = parse_var)]
+        vars: Vec<(String, String)>,
     },
-    /// List all projects in the config directory
+    /// Manage the project templates
+    #[command(arg_required_else_help = true)]
+    Templates {
+        #[clap(subcommand)]
+        command: TemplatesCommands,
+    },
+    /// List all projects in the config directory, and the local project
     #[command(visible_alias = "ls")]
-    List,
+    List {
+        /// Only the projects with the tag. Can be repeated to require several
+        /// tags
+        #[arg(long)]
+        tag: Vec<String>,
+        /// Show the projects' details: running state, number of windows,
+        /// root, tags and description
+        #[arg(short, long)]
+        long: bool,
+        /// Print the projects' details as JSON
+        #[arg(long)]
+        json: bool,
+    },
     /// Copy an existing project to a new one and edit it
     #[command(visible_alias = "cp")]
     Copy {
@@ -61,7 +231,173 @@ pub enum Commands {
         /// New Project name or filesystem path
         #[arg(required = true)]
         new: String,
+        /// Set the new project's root, moving the window roots inside the
+        /// existing one
+        #[arg(long, value_name = "DIR")]
+        root: Option<String>,
+    },
+    /// Rename a project, and its session if running
+    #[command(visible_alias = "mv", arg_required_else_help = true)]
+    Rename {
+        /// Existing Project name or filesystem path
+        #[arg(required = true)]
+        existing: String,
+        /// New Project name or filesystem path
+        #[arg(required = true)]
+        new: String,
+        /// Set the project's root, moving the window roots inside the old
+        /// one
+        #[arg(long, value_name = "DIR")]
+        root: Option<String>,
+    },
+    /// Import a project from another session manager's config file
+    #[command(arg_required_else_help = true)]
+    Import {
+        /// Format of the file
+        #[arg(long, value_enum)]
+        from: ImportFormat,
+        /// The file to import
+        file: String,
+        /// Project name or filesystem path, defaults to the session's name
+        name: Option<String>,
     },
+    /// Export a project to another session manager's format
+    #[command(arg_required_else_help = true)]
+    Export {
+        /// Format to export to
+        #[arg(long, value_enum)]
+        to: ExportFormat,
+        /// Project name or filesystem path
+        project: String,
+    },
+    /// Trust a project file outside the config directory, allowing to run it
+    Allow {
+        /// Project file path, or a directory with a local project file.
+        /// Defaults to the local project file
+        project: Option<String>,
+    },
+    /// Deny running a project file outside the config directory
+    Deny {
+        /// Project file path, or a directory with a local project file.
+        /// Defaults to the local project file
+        project: Option<String>,
+    },
+    /// List the allowed and denied project files
+    Trusted,
     /// Check your environment's configuration
     Doctor,
 }
+
+/// Subcommands of the `templates` command.
+#[derive(Debug, Subcommand)]
+pub enum TemplatesCommands {
+    /// List the templates and their variables
+    #[command(visible_alias = "ls")]
+    List,
+    /// Print a template
+    #[command(arg_required_else_help = true)]
+    Show {
+        /// Template name
+        name: String,
+    },
+    /// Edit a template, creating it if it doesn't exist
+    #[command(arg_required_else_help = true)]
+    Edit {
+        /// Template name
+        name: String,
+    },
+}
+
+/// Parses a template variable given as `NAME=VALUE`.
+fn parse_var(var: &str) -> Result<(String, String), String> {
+    match var.split_once('=') {
+        Some((name, value)) if !name.is_empty() => Ok((name.to_string(), value.to_string())),
+        _ => Err(format!("expected NAME=VALUE, got `{var}`")),
+    }
+}
+
+/// Output formats of the `debug` command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DebugFormat {
+    /// Shell script
+    Bash,
+    /// Structured steps, for tooling
+    Json,
+    /// tmux configuration, for `tmux source-file`
+    TmuxConf,
+}
+
+/// Options for running a project, shared by `run` and `open`.
+#[derive(Debug, Args)]
+pub struct RunArgs {
+    /// Attach to the session once it's ready (default)
+    #[arg(long, overrides_with = "no_attach")]
+    pub attach: bool,
+    /// Create the session detached, without attaching to it
+    #[arg(long, overrides_with = "attach")]
+    pub no_attach: bool,
+    /// Attach to the session in read-only mode
+    #[arg(short, long)]
+    pub read_only: bool,
+    /// Detach other clients attached to the session
+    #[arg(short, long)]
+    pub detach_others: bool,
+    /// Wait for the commands of the pane to exit, and exit with their
+    /// status. Can be repeated
+    #[arg(long, value_name = "WINDOW[.PANE]")]
+    pub wait: Vec<String>,
+    /// Don't check the project file against the trust store, e.g. in CI
+    #[arg(long)]
+    pub trust: bool,
+}
+
+/// Formats of the `import` command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ImportFormat {
+    /// tmuxp session file
+    Tmuxp,
+    /// teamocil layout file
+    Teamocil,
+    /// smug project file
+    Smug,
+}
+
+/// Formats of the `export` command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// tmuxp session file
+    Tmuxp,
+    /// Zellij KDL layout
+    Zellij,
+}
+
+/// Overrides of the tmux settings, read from the installed tmux if not set.
+#[derive(Debug, Args)]
+pub struct TmuxArgs {
+    /// Tmux's `base-index` option
+    #[arg(long, value_name = "INDEX")]
+    pub base_index: Option<usize>,
+    /// Tmux's `pane-base-index` option
+    #[arg(long, value_name = "INDEX")]
+    pub pane_base_index: Option<usize>,
+    /// Tmux's version, e.g. 3.3
+    #[arg(long, value_name = "VERSION")]
+    pub tmux_version: Option<TmuxVersion>,
+}
+
+/// Options for sending keys to panes, shared by `send` and `exec`.
+#[derive(Debug, Args)]
+pub struct SendArgs {
+    /// Send the keys literally, without looking up key names like `C-c`
+    #[arg(long)]
+    pub literal: bool,
+    /// Don't press Enter after sending the keys
+    #[arg(long)]
+    pub no_enter: bool,
+    /// Wait until the new output of the pane(s) matches the regex
+    #[arg(long, value_name = "REGEX")]
+    pub wait_for_output: Option<String>,
+    /// Seconds to wait for the output before failing
+    #[arg(long, default_value_t = 30, requires = "wait_for_output")]
+    pub timeout: u64,
+}
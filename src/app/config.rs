@@ -1,73 +1,428 @@
 //! Configuration directory path helpers.
-use directories::ProjectDirs;
+use directories::{BaseDirs, ProjectDirs};
 use serde::Deserialize;
 use serde_yaml::Value;
-use std::fs::{create_dir_all, File};
-use std::io::prelude::*;
-use std::path::{Path, PathBuf, MAIN_SEPARATOR};
+use std::env;
+use std::ffi::OsStr;
+use std::fs::{create_dir_all, read_dir, remove_dir, File};
+use std::io::{prelude::*, ErrorKind};
+use std::path::{Component, Path, PathBuf, MAIN_SEPARATOR};
+use std::sync::OnceLock;
 
-use crate::app::config;
+use crate::app::settings::{Settings, SETTINGS_FILE};
 use crate::error::AppError;
-use glob::glob;
 
-/// Returns the path of a file/pattern inside the configuration directory.
-pub fn get_path(pattern: &str) -> Result<PathBuf, AppError> {
+/// The directory given with `--config-dir`, searched first.
+static CONFIG_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// The environment variable with extra directories to search for projects,
+/// colon-separated.
+pub const CONFIG_ENV: &str = "RUSMUX_CONFIG";
+
+/// Sets the directory given with `--config-dir`, see [`search_path`].
+pub fn set_config_dir(dir: PathBuf) {
+    let _ = CONFIG_DIR.set(dir);
+}
+
+/// Returns the default configuration directory, e.g. `~/.config/rusmux`.
+pub fn get_config_dir() -> Result<PathBuf, AppError> {
     let proj_dirs =
         ProjectDirs::from("org", crate_authors!(), crate_name!()).ok_or(AppError::ConfigPath)?;
-    let config_dir = proj_dirs.config_dir();
+    Ok(proj_dirs.config_dir().to_path_buf())
+}
+
+/// Returns the path of a file/pattern inside the default configuration
+/// directory.
+pub fn get_path(pattern: &str) -> Result<PathBuf, AppError> {
+    Ok(get_config_dir()?.join(pattern))
+}
+
+/// Returns the directories searched for projects, by precedence:
+///
+/// 1. The directory given with `--config-dir`.
+/// 2. The directories in `$RUSMUX_CONFIG`.
+/// 3. The default configuration directory, see [`get_config_dir`].
+/// 4. tmuxinator's `~/.config/tmuxinator` and `~/.tmuxinator`, if enabled
+///    with the `tmuxinator_dirs` setting.
+///
+/// New projects are created in the first one.
+pub fn search_path() -> Result<Vec<PathBuf>, AppError> {
+    let tmuxinator_dirs = match Settings::get().tmuxinator_dirs {
+        true => BaseDirs::new()
+            .map(|dirs| {
+                vec![
+                    dirs.config_dir().join("tmuxinator"),
+                    dirs.home_dir().join(".tmuxinator"),
+                ]
+            })
+            .unwrap_or_default(),
+        false => vec![],
+    };
+    Ok(build_search_path(
+        CONFIG_DIR.get().map(PathBuf::as_path),
+        env::var_os(CONFIG_ENV).as_deref(),
+        get_config_dir()?,
+        tmuxinator_dirs,
+    ))
+}
+
+/// Helper for [`search_path`], returns the directories in order, without
+/// duplicates.
+fn build_search_path(
+    config_dir: Option<&Path>,
+    config_env: Option<&OsStr>,
+    default_dir: PathBuf,
+    tmuxinator_dirs: Vec<PathBuf>,
+) -> Vec<PathBuf> {
+    let env_dirs = config_env
+        .map(|dirs| env::split_paths(dirs).collect::<Vec<_>>())
+        .unwrap_or_default();
+    let mut dirs: Vec<PathBuf> = vec![];
+    let candidates = config_dir
+        .map(Path::to_path_buf)
+        .into_iter()
+        .chain(env_dirs)
+        .chain([default_dir])
+        .chain(tmuxinator_dirs);
+    for dir in candidates {
+        if !dir.as_os_str().is_empty() && !dirs.contains(&dir) {
+            dirs.push(dir);
+        }
+    }
+    dirs
+}
 
-    let mut path = PathBuf::from(config_dir);
-    create_dir_all(&path).map_err(|e| AppError::ProjectCreateConfigDir(path.clone(), e))?;
-    path.push(pattern);
-    Ok(path)
+/// Returns `true` if the name is a project's name, which can be namespaced by
+/// the subdirectories of the configuration directory (e.g. `work/api`),
+/// rather than a path to a file.
+pub fn is_project_name(name: &str) -> bool {
+    let path = Path::new(name);
+    path.extension().is_none()
+        && !name.starts_with('~')
+        && !name.contains('$')
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
 }
 
-/// If the name contains an filename with an extension or path parameters, treats it's
-/// like a path to the file.
+/// If the name contains an filename with an extension, or is an absolute or
+/// relative (e.g. `./session`) path, treats it's like a path to the file.
 ///
-/// Otherwise returns the path of a project file under the config dir, adding `.yml` extension it.
+/// Otherwise returns the path of a project file, adding `.yml` extension it, in the
+/// first directory of the [`search_path`] containing it. A namespaced name
+/// (e.g. `work/api`) which isn't found falls back to an existing file with
+/// the same path. If none is found, returns its path in the first directory.
+///
+/// The settings file is skipped, and fails with
+/// [`AppError::ReservedProjectName`] as the path of a new project.
 pub fn get_project_path(project_or_file_name: &str) -> Result<PathBuf, AppError> {
-    let has_extension = Path::new(project_or_file_name).extension().is_some();
-    let has_seperator = project_or_file_name.contains(MAIN_SEPARATOR);
-    if has_extension || has_seperator {
+    if !is_project_name(project_or_file_name) {
         let file_path = shellexpand::full(project_or_file_name)?;
         return Ok(PathBuf::from(file_path.as_ref()));
     }
-    let mut file_path = get_path(project_or_file_name)?;
-    file_path.set_extension("yml");
+    let file_name = format!("{project_or_file_name}.yml");
+    let dirs = search_path()?;
+    let settings_file = get_path(SETTINGS_FILE)?;
+    if let Some(file_path) = dirs
+        .iter()
+        .map(|dir| dir.join(&file_name))
+        .find(|path| path.is_file() && *path != settings_file)
+    {
+        return Ok(file_path);
+    }
+    let path = Path::new(project_or_file_name);
+    if project_or_file_name.contains(MAIN_SEPARATOR) && path.is_file() {
+        return Ok(path.to_path_buf());
+    }
 
+    let file_path = dirs[0].join(file_name);
+    if file_path == settings_file {
+        return Err(AppError::ReservedProjectName(
+            project_or_file_name.to_string(),
+            settings_file,
+        ));
+    }
     Ok(file_path)
 }
 
-/// Read project file, parse it to [`serde_yaml::Value`].
-pub fn get_project_yaml(project_name: &str) -> Result<Value, AppError> {
-    let project_file_path = get_project_path(project_name)?;
+/// Creates the directory of a project file about to be written, including
+/// the intermediate directories of a namespace, if needed.
+pub fn create_project_dir(project_file_path: &Path) -> Result<(), AppError> {
+    match project_file_path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => {
+            create_dir_all(dir).map_err(|e| AppError::ProjectCreateConfigDir(dir.to_path_buf(), e))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// The names of local project files, looked up in a project's directory.
+pub const LOCAL_PROJECT_FILES: [&str; 2] = [".rusmux.yml", ".tmuxinator.yml"];
+
+/// Returns the local project file in the directory, if exists.
+pub fn get_local_project_file(dir: &Path) -> Option<PathBuf> {
+    LOCAL_PROJECT_FILES
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// Searches the directory and its ancestors for a local project file,
+/// stopping at the home directory or the root of a git repository.
+pub fn find_local_project(start: &Path) -> Option<PathBuf> {
+    let home = BaseDirs::new().map(|dirs| dirs.home_dir().to_path_buf());
+    for dir in start.ancestors() {
+        if let Some(path) = get_local_project_file(dir) {
+            return Some(path);
+        }
+        if dir.join(".git").exists() || Some(dir) == home.as_deref() {
+            break;
+        }
+    }
+    None
+}
+
+/// Reads the content of a project file.
+pub fn read_project_file(path: &Path) -> Result<String, AppError> {
     let mut contents = String::new();
-    File::open(&project_file_path)
-        .map_err(|_| AppError::ProjectFileNotFound(project_file_path.clone()))?
+    File::open(path)
+        .map_err(|_| AppError::ProjectFileNotFound(path.to_path_buf()))?
         .read_to_string(&mut contents)
-        .map_err(|e| AppError::ProjectFileRead(project_file_path.clone(), e))?;
+        .map_err(|e| AppError::ProjectFileRead(path.to_path_buf(), e))?;
+    Ok(contents)
+}
 
-    let de = serde_yaml::Deserializer::from_str(&contents);
-    Value::deserialize(de)
-        .map_err(|e| AppError::YamlParse(project_file_path.clone(), format!("{e}")))
+/// Parses the content of a project file to [`serde_yaml::Value`].
+pub fn parse_project_yaml(path: &Path, contents: &str) -> Result<Value, AppError> {
+    let de = serde_yaml::Deserializer::from_str(contents);
+    Value::deserialize(de).map_err(|e| AppError::YamlParse(path.to_path_buf(), format!("{e}")))
 }
 
-/// Get existing projects in the configuration directory.
-pub fn get_projects() -> Result<Vec<String>, AppError> {
-    let pattern = config::get_path("*.yml")?;
+/// Removes the namespace directories of a deleted project file which were
+/// left empty, up to the directory of the [`search_path`] containing it.
+pub fn remove_empty_project_dirs(project_file_path: &Path) -> Result<(), AppError> {
+    let dirs = search_path()?;
+    let Some(root) = dirs.iter().find(|dir| project_file_path.starts_with(dir)) else {
+        return Ok(());
+    };
+    for dir in project_file_path.ancestors().skip(1) {
+        if dir == root || remove_dir(dir).is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
 
-    let projects = glob(&pattern.to_string_lossy())?
-        .filter_map(|path| {
-            if let Ok(path) = path {
-                path.file_stem()
-                    .map(|stem| stem.to_string_lossy().into_owned())
-            } else {
-                println!("{path:?}");
-                None
-            }
-        })
-        .collect();
+/// Returns `true` if the file is inside one of the directories of the
+/// [`search_path`].
+pub fn is_config_file(path: &Path) -> bool {
+    let (Ok(dirs), Ok(path)) = (search_path(), path.canonicalize()) else {
+        return false;
+    };
+    dirs.iter()
+        .filter_map(|dir| dir.canonicalize().ok())
+        .any(|dir| path.starts_with(dir))
+}
+
+/// A project in one of the directories of the [`search_path`].
+#[derive(Debug, PartialEq, Eq)]
+pub struct ConfigProject {
+    /// The project's name, namespaced by its subdirectories, e.g. `work/api`.
+    pub name: String,
+    /// The directory of the search path containing the project file.
+    pub dir: PathBuf,
+    /// The directory of the project with the same name which takes
+    /// precedence, if any.
+    pub shadowed_by: Option<PathBuf>,
+}
+
+/// Get existing projects in the directories of the [`search_path`] and their
+/// subdirectories, skipping the settings file (only in the default
+/// configuration directory), hidden directories and the template directory.
+/// Sorted by name, and by precedence for projects with the same name.
+pub fn get_projects() -> Result<Vec<ConfigProject>, AppError> {
+    let template_dir = Settings::get().template_dir()?;
+    get_projects_in(&search_path()?, &template_dir, &get_path(SETTINGS_FILE)?)
+}
+
+/// Helper for [`get_projects`], returns the projects in the directories,
+/// skipping the `excluded` directory and the settings file.
+fn get_projects_in(
+    dirs: &[PathBuf],
+    excluded: &Path,
+    settings_file: &Path,
+) -> Result<Vec<ConfigProject>, AppError> {
+    let excluded = excluded.canonicalize().ok();
+    let mut projects: Vec<ConfigProject> = vec![];
+    for dir in dirs {
+        let mut files = vec![];
+        find_project_files(dir, excluded.as_deref(), &mut files);
+        let names = files
+            .iter()
+            .filter(|path| *path != settings_file)
+            .filter_map(|path| {
+                let name = path.strip_prefix(dir).ok()?.with_extension("");
+                Some(name.to_string_lossy().replace(MAIN_SEPARATOR, "/"))
+            });
+        for name in names {
+            let shadowed_by = projects
+                .iter()
+                .find(|project| project.name == name && project.shadowed_by.is_none())
+                .map(|project| project.dir.clone());
+            projects.push(ConfigProject {
+                name,
+                dir: dir.clone(),
+                shadowed_by,
+            });
+        }
+    }
+    // A stable sort, keeping the order of precedence
+    projects.sort_by(|a, b| a.name.cmp(&b.name));
 
     Ok(projects)
 }
+
+/// Collects the `.yml` files in the directory and its subdirectories,
+/// skipping hidden ones and the (canonical) `excluded` one. A missing
+/// directory has no files.
+fn find_project_files(dir: &Path, excluded: Option<&Path>, files: &mut Vec<PathBuf>) {
+    let entries = match read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == ErrorKind::NotFound => return,
+        Err(e) => {
+            eprintln!("Can not read {}: {e}", dir.display());
+            return;
+        }
+    };
+    for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+        let is_hidden = path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+        if is_hidden {
+            continue;
+        }
+        if path.is_dir() {
+            let is_excluded =
+                excluded.is_some_and(|excluded| path.canonicalize().is_ok_and(|p| p == excluded));
+            if !is_excluded {
+                find_project_files(&path, excluded, files);
+            }
+        } else if path.extension() == Some("yml".as_ref()) {
+            files.push(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{create_dir_all, remove_dir_all};
+
+    #[test]
+    fn search_path_precedence() {
+        let dirs = build_search_path(
+            Some(Path::new("/flag")),
+            Some(OsStr::new("/env1::/env2:/flag")),
+            PathBuf::from("/xdg"),
+            vec![PathBuf::from("/home/.tmuxinator")],
+        );
+        let expected = ["/flag", "/env1", "/env2", "/xdg", "/home/.tmuxinator"];
+        assert_eq!(dirs, expected.map(PathBuf::from));
+
+        let dirs = build_search_path(None, None, PathBuf::from("/xdg"), vec![]);
+        assert_eq!(dirs, vec![PathBuf::from("/xdg")]);
+    }
+
+    #[test]
+    fn projects_collisions() {
+        let root = std::env::temp_dir().join(format!("rusmux-search.{}", std::process::id()));
+        let (first, second) = (root.join("first"), root.join("second"));
+        create_dir_all(first.join("work/.hidden")).unwrap();
+        create_dir_all(second.join("work")).unwrap();
+        let files = [
+            "first/b.yml",
+            "first/config.yml",
+            "first/work/api.yml",
+            "first/work/config.yml",
+            "first/work/.hidden/c.yml",
+            "second/a.yml",
+            "second/b.yml",
+            "second/config.yml",
+            "second/work/api.yml",
+        ];
+        for path in files {
+            File::create(root.join(path)).unwrap();
+        }
+
+        let projects = get_projects_in(
+            &[first.clone(), second.clone()],
+            &root.join("templates"),
+            &first.join("config.yml"),
+        );
+        remove_dir_all(&root).unwrap();
+        let project = |name: &str, dir: &PathBuf, shadowed_by: Option<&PathBuf>| ConfigProject {
+            name: name.into(),
+            dir: dir.clone(),
+            shadowed_by: shadowed_by.cloned(),
+        };
+        assert_eq!(
+            projects.unwrap(),
+            vec![
+                project("a", &second, None),
+                project("b", &first, None),
+                project("b", &second, Some(&first)),
+                project("config", &second, None),
+                project("work/api", &first, None),
+                project("work/api", &second, Some(&first)),
+                project("work/config", &first, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn projects_skip_template_dir() {
+        let root = std::env::temp_dir().join(format!("rusmux-templates.{}", std::process::id()));
+        create_dir_all(root.join("templates")).unwrap();
+        File::create(root.join("blog.yml")).unwrap();
+        File::create(root.join("templates/rails.yml")).unwrap();
+
+        let projects = get_projects_in(
+            std::slice::from_ref(&root),
+            &root.join("templates"),
+            &root.join("config.yml"),
+        );
+        remove_dir_all(&root).unwrap();
+        let names: Vec<_> = projects.unwrap().into_iter().map(|p| p.name).collect();
+        assert_eq!(names, vec!["blog"]);
+    }
+
+    #[test]
+    fn project_names() {
+        assert!(is_project_name("demo"));
+        assert!(is_project_name("work/api"));
+        assert!(!is_project_name("demo.yml"));
+        assert!(!is_project_name("./demo"));
+        assert!(!is_project_name("/src/demo"));
+        assert!(!is_project_name("~/demo"));
+        assert!(!is_project_name("$HOME/demo"));
+        assert!(!is_project_name("work/../demo"));
+    }
+
+    #[test]
+    fn find_local_projects() {
+        let root = std::env::temp_dir().join(format!("rusmux-local.{}", std::process::id()));
+        let nested = root.join("repo/src/module");
+        create_dir_all(&nested).unwrap();
+        File::create(root.join(".rusmux.yml")).unwrap();
+
+        // Found in an ancestor, unless a git root stops the search
+        assert_eq!(find_local_project(&nested), Some(root.join(".rusmux.yml")));
+        create_dir_all(root.join("repo/.git")).unwrap();
+        assert_eq!(find_local_project(&nested), None);
+
+        File::create(root.join("repo/.tmuxinator.yml")).unwrap();
+        let found = find_local_project(&nested);
+        remove_dir_all(&root).unwrap();
+        assert_eq!(found, Some(root.join("repo/.tmuxinator.yml")));
+    }
+}
@@ -0,0 +1,69 @@
+//! YAML files kept in the data directory, e.g. the trust store and the
+//! history.
+use crate::error::AppError;
+use directories::ProjectDirs;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs::{create_dir_all, read_to_string, write};
+use std::path::{Path, PathBuf};
+
+/// Returns the path of a file in the data directory, e.g.
+/// `~/.local/share/rusmux/<file_name>`.
+pub fn path(file_name: &str) -> Result<PathBuf, AppError> {
+    let proj_dirs =
+        ProjectDirs::from("org", crate_authors!(), crate_name!()).ok_or(AppError::ConfigPath)?;
+    Ok(proj_dirs.data_local_dir().join(file_name))
+}
+
+/// Reads a file of the data directory, the default value if it doesn't
+/// exist.
+pub fn load<T: DeserializeOwned + Default>(file_name: &str) -> Result<T, AppError> {
+    load_from(&path(file_name)?)
+}
+
+/// Writes a file of the data directory, creating the directory if needed.
+pub fn save<T: Serialize>(file_name: &str, value: &T) -> Result<(), AppError> {
+    save_to(&path(file_name)?, value)
+}
+
+/// Helper reading the YAML file, see [`load`].
+fn load_from<T: DeserializeOwned + Default>(path: &Path) -> Result<T, AppError> {
+    if !path.exists() {
+        return Ok(T::default());
+    }
+    let contents =
+        read_to_string(path).map_err(|e| AppError::DataFileRead(path.to_path_buf(), e))?;
+    serde_yaml::from_str(&contents)
+        .map_err(|e| AppError::YamlParse(path.to_path_buf(), format!("{e}")))
+}
+
+/// Helper writing the YAML file, see [`save`].
+fn save_to<T: Serialize>(path: &Path, value: &T) -> Result<(), AppError> {
+    if let Some(dir) = path.parent() {
+        create_dir_all(dir).map_err(|e| AppError::DataFileWrite(dir.to_path_buf(), e))?;
+    }
+    let contents = serde_yaml::to_string(value).map_err(AppError::ProjectSerialize)?;
+    write(path, contents).map_err(|e| AppError::DataFileWrite(path.to_path_buf(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{load_from, save_to};
+    use std::collections::BTreeMap;
+    use std::fs::remove_dir_all;
+
+    #[test]
+    fn roundtrip() {
+        let dir = std::env::temp_dir().join(format!("rusmux-data.{}", std::process::id()));
+        let path = dir.join("nested").join("store.yml");
+
+        let empty: BTreeMap<String, u64> = load_from(&path).unwrap();
+        assert!(empty.is_empty());
+
+        let value = BTreeMap::from([("/src/demo/.rusmux.yml".to_string(), 42)]);
+        save_to(&path, &value).unwrap();
+        assert_eq!(load_from::<BTreeMap<String, u64>>(&path).unwrap(), value);
+
+        remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,145 @@
+//! Project groups, started or stopped together, e.g. `rusmux run @morning`.
+//!
+//! The groups are defined in the `groups` section of the global settings:
+//!
+//! ```yaml
+//! groups:
+//!   morning:
+//!     projects:
+//!       - mail
+//!       - project: api
+//!         args: --wait build
+//!       - blog
+//!     on_start: notify-send "Good morning"
+//!     on_stop: notify-send "Good night"
+//!     attach: api
+//! ```
+use super::cli::RunArgs;
+use super::settings::Settings;
+use crate::error::AppError;
+use crate::project_config::stringorvec;
+use clap::Parser;
+use serde::Deserialize;
+
+/// The prefix of a group's name on the command line.
+pub const GROUP_PREFIX: char = '@';
+
+/// A group of projects.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct Group {
+    /// The projects, started in order.
+    pub projects: Vec<GroupMember>,
+    /// Commands to run before starting the projects.
+    #[serde(deserialize_with = "stringorvec::deserialize_optional_vec_or_string")]
+    pub on_start: Option<Vec<String>>,
+    /// Commands to run after stopping the projects.
+    #[serde(deserialize_with = "stringorvec::deserialize_optional_vec_or_string")]
+    pub on_stop: Option<Vec<String>>,
+    /// The project to attach to once they're started.
+    pub attach: Option<String>,
+}
+
+/// A project of a group, its name (or file path) and optionally the
+/// arguments of `rusmux run` to start it with.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum GroupMember {
+    Name(String),
+    WithArgs {
+        project: String,
+        #[serde(default)]
+        args: String,
+    },
+}
+
+/// Parser of a member's arguments.
+#[derive(Debug, Parser)]
+#[command(no_binary_name = true)]
+struct MemberArgs {
+    #[command(flatten)]
+    run: RunArgs,
+}
+
+/// Returns the group's name if the project's name refers to a group, i.e.
+/// starts with [`GROUP_PREFIX`].
+pub fn group_name(project_name: &str) -> Option<&str> {
+    project_name.strip_prefix(GROUP_PREFIX)
+}
+
+impl Group {
+    /// Returns the group from the global settings.
+    pub fn get(name: &str) -> Result<&'static Self, AppError> {
+        Settings::get()
+            .groups
+            .get(name)
+            .ok_or_else(|| AppError::GroupNotFound(name.to_string()))
+    }
+}
+
+impl GroupMember {
+    /// The project's name or file path.
+    pub fn project(&self) -> &str {
+        match self {
+            Self::Name(project) | Self::WithArgs { project, .. } => project,
+        }
+    }
+
+    /// Parses the member's arguments.
+    pub fn run_args(&self, group_name: &str) -> Result<RunArgs, AppError> {
+        let args = match self {
+            Self::Name(_) => "",
+            Self::WithArgs { args, .. } => args,
+        };
+        let invalid = |reason: String| {
+            AppError::GroupMemberArgs(group_name.to_string(), self.project().to_string(), reason)
+        };
+        let args = shlex::split(args).ok_or_else(|| invalid("unbalanced quotes".into()))?;
+        MemberArgs::try_parse_from(args)
+            .map(|member| member.run)
+            .map_err(|e| {
+                let message = e.to_string();
+                let first_line = message.lines().next().unwrap_or_default();
+                invalid(first_line.trim_start_matches("error: ").to_string())
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{group_name, Group, GroupMember};
+    use crate::error::AppError;
+
+    #[test]
+    fn parse_group() {
+        let group: Group = serde_yaml::from_str(
+            "projects:\n  - mail\n  - project: api\n    args: --trust --wait 'build.1'\non_start: echo start\nattach: api\n",
+        )
+        .unwrap();
+        assert_eq!(group.projects.len(), 2);
+        assert_eq!(group.on_start, Some(vec!["echo start".to_string()]));
+        assert_eq!(group.on_stop, None);
+        assert_eq!(group.attach.as_deref(), Some("api"));
+
+        let mail = &group.projects[0];
+        assert_eq!(mail, &GroupMember::Name("mail".to_string()));
+        assert!(!mail.run_args("morning").unwrap().trust);
+        let api = &group.projects[1];
+        assert_eq!(api.project(), "api");
+        let args = api.run_args("morning").unwrap();
+        assert!(args.trust);
+        assert_eq!(args.wait, vec!["build.1".to_string()]);
+
+        let invalid = GroupMember::WithArgs {
+            project: "api".to_string(),
+            args: "--watch".to_string(),
+        };
+        assert!(matches!(
+            invalid.run_args("morning"),
+            Err(AppError::GroupMemberArgs(group, project, _)) if group == "morning" && project == "api"
+        ));
+
+        assert_eq!(group_name("@morning"), Some("morning"));
+        assert_eq!(group_name("morning"), None);
+    }
+}
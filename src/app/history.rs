@@ -0,0 +1,73 @@
+//! The history of the projects' use, ordering the project picker by the most
+//! recently used.
+use super::data;
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The name of the history file, in the data directory.
+pub const HISTORY_FILE: &str = "history.yml";
+
+/// The history, mapping the canonical paths of project files to the time
+/// they were last used (seconds since the epoch).
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct History {
+    last_used: BTreeMap<PathBuf, u64>,
+}
+
+impl History {
+    /// Reads the history, an empty one if it doesn't exist.
+    pub fn load() -> Result<Self, AppError> {
+        data::load(HISTORY_FILE)
+    }
+
+    /// Writes the history, creating the data directory if needed.
+    pub fn save(&self) -> Result<(), AppError> {
+        data::save(HISTORY_FILE, self)
+    }
+
+    /// Records the use of the project file, now.
+    pub fn touch(&mut self, path: &Path) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        self.last_used.insert(path.to_path_buf(), now);
+    }
+
+    /// Returns the time the project file was last used, if ever.
+    pub fn last_used(&self, path: &Path) -> Option<u64> {
+        self.last_used.get(path).copied()
+    }
+
+    /// Records the use of the project file in the stored history. Failing
+    /// to, e.g. on a read-only data directory, shouldn't fail the command,
+    /// so errors are ignored.
+    pub fn record(path: &Path) {
+        let Ok(path) = path.canonicalize() else {
+            return;
+        };
+        if let Ok(mut history) = Self::load() {
+            history.touch(&path);
+            let _ = history.save();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::History;
+    use std::path::Path;
+
+    #[test]
+    fn touch_history() {
+        let path = Path::new("/home/user/.config/rusmux/demo.yml");
+        let mut history = History::default();
+        assert_eq!(history.last_used(path), None);
+
+        history.touch(path);
+        assert!(history.last_used(path).is_some_and(|time| time > 0));
+    }
+}
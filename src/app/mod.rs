@@ -2,3 +2,9 @@
 pub mod actions;
 pub mod cli;
 pub mod config;
+pub mod data;
+pub mod groups;
+pub mod history;
+pub mod settings;
+pub mod templates;
+pub mod trust;
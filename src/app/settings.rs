@@ -0,0 +1,110 @@
+//! The global settings file, `config.yml` in the configuration directory.
+use super::config;
+use super::groups::Group;
+use crate::error::AppError;
+use crate::project_config::directory::DirectorySettings;
+use crate::project_config::ProjectDefaults;
+use crate::tmux::TmuxServer;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs::read_to_string;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// The name of the settings file, which isn't a project.
+pub const SETTINGS_FILE: &str = "config.yml";
+
+/// The settings loaded with [`Settings::init`].
+static SETTINGS: OnceLock<Settings> = OnceLock::new();
+
+/// The global settings, all of them are optional. For example:
+///
+/// ```yaml
+/// defaults:
+///   layout: main-vertical
+/// tmux:
+///   socket: work
+/// editor: code --wait
+/// template_dir: ~/src/dotfiles/rusmux-templates
+/// directory:
+///   windows:
+///     - editor: vim
+///     - shell:
+/// groups:
+///   morning:
+///     projects: [mail, api]
+///     attach: api
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Defaults for the projects' settings, see [`ProjectDefaults`].
+    pub defaults: ProjectDefaults,
+    /// The tmux binary and server socket, see [`TmuxServer`].
+    pub tmux: TmuxServer,
+    /// The editor command, with its arguments, takes precedence over
+    /// `$VISUAL` and `$EDITOR`.
+    pub editor: Option<String>,
+    /// The directory of the project templates, defaults to `templates` in
+    /// the configuration directory.
+    pub template_dir: Option<String>,
+    /// Settings for the projects of directories, see [`DirectorySettings`].
+    pub directory: DirectorySettings,
+    /// Search tmuxinator's directories for projects as well, see
+    /// [`config::search_path`].
+    pub tmuxinator_dirs: bool,
+    /// The project groups by name, see [`Group`].
+    pub groups: BTreeMap<String, Group>,
+}
+
+impl Settings {
+    /// Reads the settings file, returns the defaults if it doesn't exist.
+    pub fn load() -> Result<Self, AppError> {
+        let path = config::get_path(SETTINGS_FILE)?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents =
+            read_to_string(&path).map_err(|e| AppError::SettingsFileRead(path.clone(), e))?;
+        serde_yaml::from_str(&contents).map_err(|e| AppError::YamlParse(path, format!("{e}")))
+    }
+
+    /// Reads the settings file once, on startup, and sets the tmux server used
+    /// by the tmux commands.
+    pub fn init() -> Result<(), AppError> {
+        let settings = Self::load()?;
+        TmuxServer::set(settings.tmux.clone());
+        let _ = SETTINGS.set(settings);
+        Ok(())
+    }
+
+    /// Returns the settings read with [`Settings::init`], or the defaults.
+    pub fn get() -> &'static Self {
+        SETTINGS.get_or_init(Self::default)
+    }
+
+    /// Returns the editor command and where it's set, by precedence: the
+    /// `editor` setting, `$VISUAL` or `$EDITOR`.
+    pub fn editor(&self) -> Option<(String, &'static str)> {
+        if let Some(editor) = &self.editor {
+            return Some((editor.clone(), "editor setting"));
+        }
+        [("VISUAL", "$VISUAL"), ("EDITOR", "$EDITOR")]
+            .into_iter()
+            .find_map(|(var, source)| {
+                env::var(var)
+                    .ok()
+                    .filter(|editor| !editor.trim().is_empty())
+                    .map(|editor| (editor, source))
+            })
+    }
+
+    /// Returns the directory of the project templates.
+    pub fn template_dir(&self) -> Result<PathBuf, AppError> {
+        match &self.template_dir {
+            Some(dir) => Ok(PathBuf::from(shellexpand::full(dir)?.as_ref())),
+            None => config::get_path("templates"),
+        }
+    }
+}
@@ -0,0 +1,428 @@
+//! Project templates, used by `rusmux new`.
+//!
+//! A template is a project file in the template directory (see
+//! [`Settings::template_dir`]), with `{{ name }}` placeholders for its
+//! variables. The variables are declared in a front matter, between `---`
+//! lines:
+//!
+//! ```yaml
+//! ---
+//! variables:
+//!   - name: ruby
+//!     prompt: Ruby version
+//!     default: "3.3"
+//!   - name: database
+//!     choices: [postgres, mysql]
+//! ---
+//! project_name: {{ project_name }}
+//! pre_window: rbenv shell {{ ruby }}
+//! ```
+//!
+//! `project_name` is always set, to the new project's name. The values are
+//! inserted verbatim, a placeholder whose value may contain YAML syntax (e.g.
+//! `: ` or `#`) has to be quoted in the template.
+use super::settings::Settings;
+use crate::error::AppError;
+use crate::project_config::ProjectConfig;
+use dialoguer::{Input, Select};
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs::{read_dir, read_to_string};
+use std::path::PathBuf;
+
+/// The name of the template used when none is given.
+pub const DEFAULT_TEMPLATE: &str = "default";
+
+/// The variable set to the new project's name.
+pub const PROJECT_NAME_VAR: &str = "project_name";
+
+/// The extension of the template files.
+const TEMPLATE_EXTENSION: &str = "yml";
+
+/// The front matter delimiter.
+const FRONT_MATTER_DELIMITER: &str = "---";
+
+/// The default template, when there's no `default.yml` in the template
+/// directory.
+const BUILTIN_TEMPLATE: &str = "project_name: {{ project_name }}
+# project_root: ~/src/project_path
+# on_project_start:
+#   - sudo systemctl start postgresql
+# pre_window:
+#   - workon dummy
+# windows:
+#   - editor: vim
+#   - shells:
+#       layout: main-vertical
+#       panes:
+#         - #
+#         - grunt serve
+";
+
+/// The front matter of a new template, created with `rusmux templates edit`.
+const NEW_TEMPLATE_FRONT_MATTER: &str = "---
+variables: []
+#  - name: ruby
+#    prompt: Ruby version
+#    default: \"3.3\"
+#  - name: database
+#    choices: [postgres, mysql]
+---
+";
+
+/// A variable of a template.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct TemplateVariable {
+    pub name: String,
+    /// The prompt shown when asking for the value, defaults to the name.
+    pub prompt: Option<String>,
+    /// The default value, may refer to the variables declared before it.
+    pub default: Option<String>,
+    /// The allowed values, any value is allowed if empty.
+    #[serde(default)]
+    pub choices: Vec<String>,
+}
+
+/// The front matter of a template.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct FrontMatter {
+    variables: Vec<TemplateVariable>,
+}
+
+/// A project template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    pub name: String,
+    /// The template's file, `None` for the built-in default template.
+    pub path: Option<PathBuf>,
+    pub variables: Vec<TemplateVariable>,
+    /// The template without its front matter.
+    pub body: String,
+}
+
+impl Template {
+    /// Returns the path of the template's file in the template directory.
+    pub fn path(name: &str) -> Result<PathBuf, AppError> {
+        Ok(Settings::get()
+            .template_dir()?
+            .join(format!("{name}.{TEMPLATE_EXTENSION}")))
+    }
+
+    /// Returns the names of the templates in the template directory, and the
+    /// default one, ordered by name.
+    pub fn names() -> Result<Vec<String>, AppError> {
+        let dir = Settings::get().template_dir()?;
+        let mut names = vec![DEFAULT_TEMPLATE.to_string()];
+        if let Ok(entries) = read_dir(&dir) {
+            for path in entries.flatten().map(|entry| entry.path()) {
+                if path.is_file()
+                    && path
+                        .extension()
+                        .is_some_and(|ext| ext == TEMPLATE_EXTENSION)
+                {
+                    if let Some(stem) = path.file_stem() {
+                        names.push(stem.to_string_lossy().into_owned());
+                    }
+                }
+            }
+        }
+        names.sort();
+        names.dedup();
+        Ok(names)
+    }
+
+    /// Reads a template from the template directory. The default template
+    /// falls back to the built-in one.
+    pub fn load(name: &str) -> Result<Self, AppError> {
+        let path = Self::path(name)?;
+        if !path.is_file() {
+            return match name {
+                DEFAULT_TEMPLATE => Ok(Self::builtin()),
+                _ => Err(AppError::TemplateNotFound(name.to_string(), path)),
+            };
+        }
+        let content =
+            read_to_string(&path).map_err(|e| AppError::TemplateFileRead(path.clone(), e))?;
+        let (front_matter, body) = split_front_matter(&content);
+        let front_matter: FrontMatter = match front_matter {
+            Some(yaml) => serde_yaml::from_str(yaml)
+                .map_err(|e| AppError::YamlParse(path.clone(), format!("{e}")))?,
+            None => FrontMatter::default(),
+        };
+        Ok(Self {
+            name: name.to_string(),
+            path: Some(path),
+            variables: front_matter.variables,
+            body: body.to_string(),
+        })
+    }
+
+    /// Returns the built-in default template.
+    pub fn builtin() -> Self {
+        Self {
+            name: DEFAULT_TEMPLATE.to_string(),
+            path: None,
+            variables: vec![],
+            body: BUILTIN_TEMPLATE.to_string(),
+        }
+    }
+
+    /// Returns the content of a new template file, the built-in template with
+    /// an example front matter.
+    pub fn new_file_content() -> String {
+        format!("{NEW_TEMPLATE_FRONT_MATTER}{BUILTIN_TEMPLATE}")
+    }
+
+    /// Returns the values of the variables for a new project. Values which
+    /// aren't given are asked for if `interactive`, otherwise their default
+    /// is used.
+    pub fn values(
+        &self,
+        project_name: &str,
+        given: &[(String, String)],
+        interactive: bool,
+    ) -> Result<BTreeMap<String, String>, AppError> {
+        if let Some((name, _)) = given
+            .iter()
+            .find(|(name, _)| !self.variables.iter().any(|v| v.name == *name))
+        {
+            return Err(AppError::UnknownTemplateVariable(
+                self.name.clone(),
+                name.clone(),
+            ));
+        }
+
+        let mut values = BTreeMap::from([(PROJECT_NAME_VAR.to_string(), project_name.to_string())]);
+        for variable in &self.variables {
+            let given = given
+                .iter()
+                .rev()
+                .find(|(name, _)| *name == variable.name)
+                .map(|(_, value)| value.clone());
+            let default = match &variable.default {
+                Some(default) => Some(self.substitute(default, &values)?),
+                None => None,
+            };
+            let value = match given {
+                Some(value) => value,
+                None if interactive => variable.ask(default)?,
+                None => default.ok_or_else(|| {
+                    AppError::MissingTemplateVariable(self.name.clone(), variable.name.clone())
+                })?,
+            };
+            if !variable.choices.is_empty() && !variable.choices.contains(&value) {
+                return Err(AppError::InvalidTemplateChoice(
+                    variable.name.clone(),
+                    value,
+                    variable.choices.join(", "),
+                ));
+            }
+            values.insert(variable.name.clone(), value);
+        }
+        Ok(values)
+    }
+
+    /// Renders the template with the values, and validates the result is a
+    /// valid project.
+    pub fn render(&self, values: &BTreeMap<String, String>) -> Result<String, AppError> {
+        let rendered = self.substitute(&self.body, values)?;
+        ProjectConfig::try_from(rendered.clone())
+            .map_err(|e| AppError::TemplateRender(self.name.clone(), e))?;
+        Ok(rendered)
+    }
+
+    /// Replaces the placeholders in the text with the values, verbatim.
+    fn substitute(
+        &self,
+        text: &str,
+        values: &BTreeMap<String, String>,
+    ) -> Result<String, AppError> {
+        let placeholder = Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_]*)\s*\}\}").unwrap();
+        if let Some(name) = placeholder
+            .captures_iter(text)
+            .map(|caps| caps[1].to_string())
+            .find(|name| !values.contains_key(name))
+        {
+            return Err(AppError::UnknownTemplateVariable(self.name.clone(), name));
+        }
+        Ok(placeholder
+            .replace_all(text, |caps: &regex::Captures| values[&caps[1]].clone())
+            .into_owned())
+    }
+}
+
+impl TemplateVariable {
+    /// Asks for the value, with a selection if there are choices.
+    fn ask(&self, default: Option<String>) -> Result<String, AppError> {
+        let prompt = self.prompt.as_deref().unwrap_or(&self.name);
+        if self.choices.is_empty() {
+            let mut input = Input::<String>::new().with_prompt(prompt);
+            if let Some(default) = default {
+                input = input.default(default);
+            }
+            return input.interact_text().map_err(AppError::Prompt);
+        }
+
+        let selected = default
+            .and_then(|default| self.choices.iter().position(|c| *c == default))
+            .unwrap_or(0);
+        let index = Select::new()
+            .with_prompt(prompt)
+            .items(&self.choices)
+            .default(selected)
+            .interact()
+            .map_err(AppError::Prompt)?;
+        Ok(self.choices[index].clone())
+    }
+}
+
+/// Splits the content to the front matter (if any) and the body.
+fn split_front_matter(content: &str) -> (Option<&str>, &str) {
+    let Some(rest) = content
+        .strip_prefix(FRONT_MATTER_DELIMITER)
+        .and_then(|rest| rest.strip_prefix('\n'))
+    else {
+        return (None, content);
+    };
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == FRONT_MATTER_DELIMITER {
+            return (Some(&rest[..offset]), &rest[offset + line.len()..]);
+        }
+        offset += line.len();
+    }
+    (None, content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{split_front_matter, Template, TemplateVariable};
+    use crate::error::AppError;
+    use std::collections::BTreeMap;
+
+    fn template() -> Template {
+        Template {
+            name: "rails".to_string(),
+            path: None,
+            variables: vec![
+                TemplateVariable {
+                    name: "root".to_string(),
+                    prompt: None,
+                    default: Some("~/src/{{ project_name }}".to_string()),
+                    choices: vec![],
+                },
+                TemplateVariable {
+                    name: "database".to_string(),
+                    prompt: Some("Database".to_string()),
+                    default: None,
+                    choices: vec!["postgres".to_string(), "mysql".to_string()],
+                },
+            ],
+            body: "project_name: {{ project_name }}\nroot: {{root}}\nwindows:\n  - db: {{ database }}\n"
+                .to_string(),
+        }
+    }
+
+    #[test]
+    fn front_matter() {
+        assert_eq!(
+            split_front_matter("---\nvariables: []\n---\nproject_name: demo\n"),
+            (Some("variables: []\n"), "project_name: demo\n")
+        );
+        assert_eq!(
+            split_front_matter("project_name: demo\n---\n"),
+            (None, "project_name: demo\n---\n")
+        );
+        assert_eq!(
+            split_front_matter("---\nproject_name: demo\n"),
+            (None, "---\nproject_name: demo\n")
+        );
+    }
+
+    #[test]
+    fn render_template() {
+        let template = template();
+        let values = template
+            .values(
+                "blog",
+                &[("database".to_string(), "mysql".to_string())],
+                false,
+            )
+            .unwrap();
+        assert_eq!(
+            values,
+            BTreeMap::from([
+                ("project_name".to_string(), "blog".to_string()),
+                ("root".to_string(), "~/src/blog".to_string()),
+                ("database".to_string(), "mysql".to_string()),
+            ])
+        );
+        assert_eq!(
+            template.render(&values).unwrap(),
+            "project_name: blog\nroot: ~/src/blog\nwindows:\n  - db: mysql\n"
+        );
+    }
+
+    #[test]
+    fn template_errors() {
+        let template = template();
+        assert!(matches!(
+            template.values("blog", &[], false),
+            Err(AppError::MissingTemplateVariable(_, name)) if name == "database"
+        ));
+        assert!(matches!(
+            template.values(
+                "blog",
+                &[("database".to_string(), "sqlite".to_string())],
+                false
+            ),
+            Err(AppError::InvalidTemplateChoice(..))
+        ));
+        assert!(matches!(
+            template.values("blog", &[("ruby".to_string(), "3.3".to_string())], false),
+            Err(AppError::UnknownTemplateVariable(_, name)) if name == "ruby"
+        ));
+
+        let values = BTreeMap::from([("project_name".to_string(), "blog".to_string())]);
+        assert!(matches!(
+            template.render(&values),
+            Err(AppError::UnknownTemplateVariable(_, name)) if name == "root"
+        ));
+        let invalid = Template {
+            body: "windows: {{ project_name }}\n".to_string(),
+            ..template
+        };
+        assert!(matches!(
+            invalid.render(&values),
+            Err(AppError::TemplateRender(..))
+        ));
+    }
+
+    #[test]
+    fn verbatim_values() {
+        let values = BTreeMap::from([
+            ("project_name".to_string(), "blog".to_string()),
+            ("description".to_string(), "Blog: the API".to_string()),
+        ]);
+        let template = |body: &str| Template {
+            body: body.to_string(),
+            ..template()
+        };
+
+        let unquoted =
+            template("project_name: {{ project_name }}\ndescription: {{ description }}\n");
+        assert!(matches!(
+            unquoted.render(&values),
+            Err(AppError::TemplateRender(..))
+        ));
+
+        let quoted =
+            template("project_name: {{ project_name }}\ndescription: \"{{ description }}\"\n");
+        assert_eq!(
+            quoted.render(&values).unwrap(),
+            "project_name: blog\ndescription: \"Blog: the API\"\n"
+        );
+    }
+}
@@ -0,0 +1,127 @@
+//! The trust store of project files outside the configuration directory.
+//!
+//! Such files (local project files, or ones given by their path) may come
+//! from anywhere, e.g. a cloned repository, so they're only run once allowed
+//! with `rusmux allow`. The store keeps the hash of the allowed content,
+//! a changed file has to be allowed again.
+use super::data;
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// The name of the trust store file, in the data directory.
+pub const TRUST_FILE: &str = "trust.yml";
+
+/// Whether a project file was allowed or denied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrustStatus {
+    Allowed,
+    Denied,
+}
+
+/// A project file's entry in the store.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrustEntry {
+    pub status: TrustStatus,
+    /// SHA-256 of the file's content when it was allowed or denied.
+    pub hash: String,
+}
+
+/// The result of checking a project file against the store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trust {
+    Allowed,
+    /// Denied with `rusmux deny`, regardless of the content.
+    Denied,
+    /// Never allowed or denied.
+    Unknown,
+    /// Allowed, but the content changed since.
+    Changed,
+}
+
+/// The trust store, mapping the canonical paths of project files to their
+/// [`TrustEntry`].
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct TrustStore {
+    entries: BTreeMap<PathBuf, TrustEntry>,
+}
+
+/// Returns the hex encoded SHA-256 of the content.
+pub fn hash(content: &[u8]) -> String {
+    Sha256::digest(content)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+impl TrustStore {
+    /// Reads the store, an empty one if it doesn't exist.
+    pub fn load() -> Result<Self, AppError> {
+        data::load(TRUST_FILE)
+    }
+
+    /// Writes the store, creating the data directory if needed.
+    pub fn save(&self) -> Result<(), AppError> {
+        data::save(TRUST_FILE, self)
+    }
+
+    /// Checks the project file's content.
+    pub fn check(&self, path: &Path, content: &[u8]) -> Trust {
+        match self.entries.get(path) {
+            None => Trust::Unknown,
+            Some(entry) if entry.status == TrustStatus::Denied => Trust::Denied,
+            Some(entry) if entry.hash == hash(content) => Trust::Allowed,
+            Some(_) => Trust::Changed,
+        }
+    }
+
+    /// Sets the project file's status for its current content.
+    pub fn set(&mut self, path: &Path, content: &[u8], status: TrustStatus) {
+        let hash = hash(content);
+        self.entries
+            .insert(path.to_path_buf(), TrustEntry { status, hash });
+    }
+
+    /// Returns the entries, ordered by path.
+    pub fn entries(&self) -> impl Iterator<Item = (&PathBuf, &TrustEntry)> {
+        self.entries.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hash, Trust, TrustStatus, TrustStore};
+    use std::path::Path;
+
+    #[test]
+    fn hash_content() {
+        assert_eq!(
+            hash(b"project_name: demo"),
+            "c20c29bc28cb88a8ad05e01500c4388bb612fd3776e454c4134b6552d74eff6f"
+        );
+    }
+
+    #[test]
+    fn check_trust() {
+        let path = Path::new("/src/demo/.rusmux.yml");
+        let content = b"project_name: demo";
+        let mut store = TrustStore::default();
+        assert_eq!(store.check(path, content), Trust::Unknown);
+
+        store.set(path, content, TrustStatus::Allowed);
+        assert_eq!(store.check(path, content), Trust::Allowed);
+        assert_eq!(store.check(path, b"project_name: changed"), Trust::Changed);
+        assert_eq!(
+            store.check(Path::new("/src/other/.rusmux.yml"), content),
+            Trust::Unknown
+        );
+
+        store.set(path, content, TrustStatus::Denied);
+        assert_eq!(store.check(path, content), Trust::Denied);
+        assert_eq!(store.check(path, b"project_name: changed"), Trust::Denied);
+    }
+}
@@ -1,4 +1,5 @@
 //! Top level error module.
+use crate::interop::InteropError;
 use crate::project_config::ProjectParseError;
 use crate::tmux::TmuxError;
 
@@ -23,6 +24,15 @@ pub enum AppError {
     /// [ProjectConfig](crate::project_config::project::ProjectConfig).
     #[error("{0}")]
     ProjectParse(#[from] ProjectParseError),
+    /// Error converting a project from another format.
+    #[error("Could not import {0}: {1}")]
+    Import(PathBuf, InteropError),
+    /// Error converting a project to another format.
+    #[error("Could not export {0}: {1}")]
+    Export(String, InteropError),
+    /// Error serializing a project to `yaml`.
+    #[error("Could not serialize project: {0}")]
+    ProjectSerialize(serde_yaml::Error),
     /// Error running `tmux` operation.
     #[error(transparent)]
     TmuxOperation(#[from] TmuxError),
@@ -44,14 +54,109 @@ pub enum AppError {
     /// Can not find the project file.
     #[error("Project file {0} not found")]
     ProjectFileNotFound(PathBuf),
+    /// The project's file would be the settings file.
+    #[error("Project name {0:?} is reserved for the settings file {1}")]
+    ReservedProjectName(String, PathBuf),
+    /// No local project file in the current directory or its ancestors.
+    #[error("No local project file (.rusmux.yml or .tmuxinator.yml) found in {0} or its parents")]
+    LocalProjectNotFound(PathBuf),
+    /// Can not get the current directory.
+    #[error("Can not get the current directory: {0}")]
+    CurrentDir(io::Error),
+    /// Can not find the directory of a directory project.
+    #[error("Directory {0} not found")]
+    DirectoryNotFound(PathBuf),
+    /// The project file outside the configuration directory was never
+    /// allowed.
+    #[error("Project file {0} is not trusted, review it and run `rusmux allow {0}` to trust it")]
+    ProjectNotTrusted(PathBuf),
+    /// The project file changed since it was allowed.
+    #[error("Project file {0} changed since it was allowed, review it and run `rusmux allow {0}` to trust it")]
+    ProjectChanged(PathBuf),
+    /// The project file was denied with `rusmux deny`.
+    #[error("Project file {0} is denied, run `rusmux allow {0}` to trust it")]
+    ProjectDenied(PathBuf),
+    /// Only project files outside the configuration directory are checked
+    /// against the trust store.
+    #[error(
+        "Nothing to trust for {0}, only project files outside the config directory are checked"
+    )]
+    NothingToTrust(PathBuf),
+    /// Error reading a file of the data directory, e.g. the trust store.
+    #[error("Could not read {0}: {1}")]
+    DataFileRead(PathBuf, io::Error),
+    /// Error writing a file of the data directory.
+    #[error("Could not write {0}: {1}")]
+    DataFileWrite(PathBuf, io::Error),
+    /// No projects to pick from.
+    #[error("No projects found, create one with `rusmux new`")]
+    NoProjects,
+    /// No projects have the tags.
+    #[error("No projects tagged {0}")]
+    NoTaggedProjects(String),
+    /// Error serializing the listed projects to JSON.
+    #[error("Could not serialize the projects: {0}")]
+    ListSerialize(serde_json::Error),
+    /// The project picker was closed without picking a project.
+    #[error("No project selected")]
+    NoProjectSelected,
+    /// `rusmux switch` outside of tmux.
+    #[error("Not running inside tmux, use `rusmux attach` instead")]
+    NotInsideTmux,
+    /// No sessions of other projects to switch to.
+    #[error("No other running projects to switch to")]
+    NoOtherRunningProjects,
+    /// The group isn't defined in the settings file.
+    #[error("Group {0} not found in the groups of the settings file")]
+    GroupNotFound(String),
+    /// Invalid `args` of a project in a group.
+    #[error("Invalid args of project {1} in group {0}: {2}")]
+    GroupMemberArgs(String, String, String),
+    /// `--wait` given for a group, rather than for its projects.
+    #[error("Can not wait for the panes of group {0}, set --wait in the args of its projects")]
+    GroupWait(String),
+    /// The project to attach to isn't one of the group's.
+    #[error("Group {0} attaches to {1}, which isn't one of its projects")]
+    GroupAttach(String, String),
+    /// Some of the projects of a group, or with the tags, failed.
+    #[error("{0} of {1} projects failed")]
+    ProjectsFailed(usize, usize),
     /// Error reading file content.
     #[error("Could not read content from project file {0}: {1}")]
     ProjectFileRead(PathBuf, io::Error),
+    /// The project file is invalid after editing it.
+    #[error("Invalid project file {0}: {1}")]
+    ProjectFileInvalid(PathBuf, ProjectParseError),
+    /// Error reading a template file.
+    #[error("Could not read template file {0}: {1}")]
+    TemplateFileRead(PathBuf, io::Error),
+    /// The template isn't in the template directory.
+    #[error("Template {0} not found, expected at {1}")]
+    TemplateNotFound(String, PathBuf),
+    /// A value is given for a variable the template doesn't declare, or the
+    /// template uses one.
+    #[error("Template {0} has no variable {1}")]
+    UnknownTemplateVariable(String, String),
+    /// No value, nor default, for a template variable.
+    #[error("Template {0} needs a value for {1}, set it with --var {1}=<value>")]
+    MissingTemplateVariable(String, String),
+    /// The value of a template variable isn't one of its choices.
+    #[error("Invalid value {1} for {0}, expected one of: {2}")]
+    InvalidTemplateChoice(String, String, String),
+    /// The rendered template isn't a valid project.
+    #[error("Template {0} renders an invalid project (values are inserted verbatim): {1}")]
+    TemplateRender(String, ProjectParseError),
+    /// Error writing a template file.
+    #[error("Could not write template file {0}: {1}")]
+    TemplateFileWrite(PathBuf, io::Error),
+    /// Error reading the settings file.
+    #[error("Could not read settings file {0}: {1}")]
+    SettingsFileRead(PathBuf, io::Error),
     /// Error writing file content.
     #[error("Could not write content to project file {0}: {1}")]
     ProjectFileWrite(PathBuf, io::Error),
-    /// `$EDITOR` environment var is not set.
-    #[error("$EDITOR is not set, the file path to edit is {0}")]
+    /// No editor is set, neither `$VISUAL`, `$EDITOR` nor the `editor` setting.
+    #[error("$VISUAL or $EDITOR (or the editor setting) is not set, the file path to edit is {0}")]
     EditorNotSet(PathBuf),
     /// Error running a command
     #[error("Could not run command {0}")]
@@ -62,8 +167,20 @@ pub enum AppError {
     /// Error getting project name from file path
     #[error("Can not get project name from file path {0}")]
     GetProjectNameFromFilePath(String),
-    #[error("Failed to glob config dir {0}")]
-    ProjectGlob(#[from] glob::PatternError),
+    /// Invalid regular expression given on the command line.
+    #[error("Invalid regex {0}: {1}")]
+    InvalidRegex(String, regex::Error),
+}
+
+impl AppError {
+    /// The process exit code to use instead of the default one, e.g. to
+    /// propagate the exit status of a waited for pane.
+    pub fn exit_code(&self) -> Option<i32> {
+        match self {
+            AppError::TmuxOperation(TmuxError::PaneExit { code, .. }) => Some(*code),
+            _ => None,
+        }
+    }
 }
 
 /// Used for displaying the error on exit.
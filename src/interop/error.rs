@@ -0,0 +1,16 @@
+//! Errors converting projects from and to other formats.
+use thiserror::Error;
+
+/// Indicates a problem converting a project from or to another format.
+#[derive(Error, Debug)]
+pub enum InteropError {
+    /// Error parsing the file of another format.
+    #[error("Cannot parse {format} config: {source}")]
+    Parse {
+        format: &'static str,
+        source: serde_yaml::Error,
+    },
+    /// Error serializing the converted project.
+    #[error("Cannot serialize project: {0}")]
+    Serialize(serde_yaml::Error),
+}
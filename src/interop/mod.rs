@@ -0,0 +1,207 @@
+//! Converts projects from and to the configuration formats of other terminal
+//! session managers.
+//!
+//! Each format has its own module, mapping the format's model to and from
+//! [ProjectConfig]. Constructs which have no equivalent are skipped, adding a
+//! warning to the [Imported] or [Exported] result.
+
+mod error;
+pub mod smug;
+pub mod teamocil;
+pub mod tmuxp;
+pub mod zellij;
+
+pub use error::InteropError;
+
+use crate::project_config::ProjectConfig;
+use crate::tmux::quote_path;
+use serde::Deserialize;
+use serde_yaml::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// A project converted from another format.
+#[derive(Debug)]
+pub struct Imported {
+    pub project: ProjectConfig,
+    /// Constructs of the source file which were skipped or changed.
+    pub warnings: Vec<String>,
+}
+
+/// A project converted to another format.
+#[derive(Debug)]
+pub struct Exported {
+    /// The content of the converted file.
+    pub content: String,
+    /// Settings of the project which were skipped or approximated.
+    pub warnings: Vec<String>,
+}
+
+/// A string or a list of strings, e.g. a single command or several commands.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum StringOrList {
+    String(String),
+    List(Vec<String>),
+}
+
+impl StringOrList {
+    fn into_vec(self) -> Vec<String> {
+        match self {
+            StringOrList::String(value) => vec![value],
+            StringOrList::List(values) => values,
+        }
+    }
+}
+
+/// Parses the yaml of the `format`.
+fn parse<'de, T: Deserialize<'de>>(
+    format: &'static str,
+    yaml: &'de str,
+) -> Result<T, InteropError> {
+    serde_yaml::from_str(yaml).map_err(|source| InteropError::Parse { format, source })
+}
+
+/// Adds a warning for each of the `unsupported` keys, which were collected
+/// by `#[serde(flatten)]` while de-serializing a section of the file.
+fn warn_unsupported(
+    warnings: &mut Vec<String>,
+    section: &str,
+    unsupported: &BTreeMap<String, Value>,
+) {
+    for key in unsupported.keys() {
+        warnings.push(format!("Skipping unsupported option {key:?} of {section}"));
+    }
+}
+
+/// Adds a warning for each of the project's settings which the other formats
+/// don't support.
+fn warn_unexported(warnings: &mut Vec<String>, project: &ProjectConfig, format: &str) {
+    let hooks = [
+        ("on_project_restart", &project.on_project_restart),
+        ("on_project_exit", &project.on_project_exit),
+        ("on_project_stop", &project.on_project_stop),
+        ("pre", &project.pre),
+        ("post", &project.post),
+    ];
+    for (name, hook) in hooks {
+        if hook.is_some() {
+            warnings.push(format!("Skipping {name}, {format} has no equivalent"));
+        }
+    }
+    let has_log = project.log.is_some()
+        || project
+            .windows
+            .iter()
+            .flatten()
+            .any(|window| window.log.is_some());
+    if has_log {
+        warnings.push(format!("Skipping log, {format} has no equivalent"));
+    }
+    if project.sessions.is_some() {
+        warnings.push(format!("Skipping sessions, {format} has no equivalent"));
+    }
+    for window in project.windows.iter().flatten() {
+        if window.link.is_some() {
+            warnings.push(format!(
+                "Skipping link of window {:?}, {format} has no equivalent",
+                window.name
+            ));
+        }
+    }
+}
+
+/// Used to skip serializing `false` flags.
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+/// Returns `root` relative to `parent` (if it's a relative path).
+fn join_root(parent: Option<&str>, root: &str) -> String {
+    match parent {
+        Some(parent) if !root.starts_with('~') && Path::new(root).is_relative() => {
+            Path::new(parent).join(root).to_string_lossy().into_owned()
+        }
+        _ => root.into(),
+    }
+}
+
+/// Returns the command changing a pane to its own directory, since panes
+/// start in the window's root.
+fn cd_command(root: &str) -> String {
+    format!("cd {}", quote_path(root))
+}
+
+/// Converts a tmux option value to a string, e.g. `true` to `on`.
+fn option_value(value: &Value) -> Option<String> {
+    match value {
+        Value::String(value) => Some(value.clone()),
+        Value::Number(value) => Some(value.to_string()),
+        Value::Bool(value) => Some(if *value { "on" } else { "off" }.into()),
+        _ => None,
+    }
+}
+
+/// Converts the window options of the formats which support them.
+fn window_options(
+    warnings: &mut Vec<String>,
+    window_name: &str,
+    options: BTreeMap<String, Value>,
+) -> Option<HashMap<String, String>> {
+    let options: HashMap<_, _> = options
+        .into_iter()
+        .filter_map(|(name, value)| match option_value(&value) {
+            Some(value) => Some((name, value)),
+            None => {
+                warnings.push(format!(
+                    "Skipping option {name:?} of window {window_name:?}, its value isn't a string, number or boolean"
+                ));
+                None
+            }
+        })
+        .collect();
+    (!options.is_empty()).then_some(options)
+}
+
+/// Warns if a window or pane other than the first one is focused, since the
+/// first window and pane are always selected.
+fn warn_focus(warnings: &mut Vec<String>, focused: &str, index: usize) {
+    if index > 0 {
+        warnings.push(format!(
+            "Focus on {focused} isn't supported, the first one is selected"
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn join_roots() {
+        assert_eq!(join_root(Some("~/src"), "app"), "~/src/app");
+        assert_eq!(join_root(Some("~/src"), "/tmp"), "/tmp");
+        assert_eq!(join_root(Some("~/src"), "~/app"), "~/app");
+        assert_eq!(join_root(None, "app"), "app");
+    }
+
+    #[test]
+    fn option_values() {
+        let yaml: Value = serde_yaml::from_str("[70%, 35, true, [a]]").unwrap();
+        let values: Vec<_> = yaml
+            .as_sequence()
+            .unwrap()
+            .iter()
+            .map(option_value)
+            .collect();
+        assert_eq!(
+            values,
+            vec![
+                Some("70%".into()),
+                Some("35".into()),
+                Some("on".into()),
+                None
+            ]
+        );
+    }
+}
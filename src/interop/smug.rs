@@ -0,0 +1,211 @@
+//! Imports [smug](https://github.com/ivaaaan/smug) project files.
+//!
+//! For example:
+//!
+//! ```yaml
+//! session: demo
+//! root: ~/src/demo
+//! before_start:
+//!   - docker compose up -d
+//! stop:
+//!   - docker compose stop
+//! windows:
+//!   - name: code
+//!     layout: main-vertical
+//!     commands:
+//!       - vim
+//!     panes:
+//!       - type: horizontal
+//!         root: docs
+//!         commands:
+//!           - make html
+//! ```
+//!
+//! `before_start` is run as `on_project_first_start`, and `stop` as
+//! `on_project_stop`. The window's `commands` run in its first pane, and the
+//! `panes` are split from it. Relative roots are joined with the parent's
+//! root, and a pane's root is changed into with `cd`.
+use super::{cd_command, join_root, parse, warn_unsupported, Imported, InteropError, StringOrList};
+use crate::project_config::{ProjectConfig, Window};
+use serde::Deserialize;
+use serde_yaml::Value;
+use std::collections::BTreeMap;
+
+const FORMAT: &str = "smug";
+
+#[derive(Debug, Deserialize)]
+struct Config {
+    session: Option<String>,
+    root: Option<String>,
+    before_start: Option<StringOrList>,
+    stop: Option<StringOrList>,
+    #[serde(default)]
+    windows: Vec<SmugWindow>,
+    #[serde(flatten)]
+    unsupported: BTreeMap<String, Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SmugWindow {
+    name: Option<String>,
+    root: Option<String>,
+    layout: Option<String>,
+    #[serde(default)]
+    manual: bool,
+    commands: Option<StringOrList>,
+    #[serde(default)]
+    panes: Vec<Pane>,
+    #[serde(flatten)]
+    unsupported: BTreeMap<String, Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Pane {
+    root: Option<String>,
+    #[serde(rename = "type")]
+    split_type: Option<String>,
+    commands: Option<StringOrList>,
+    #[serde(flatten)]
+    unsupported: BTreeMap<String, Value>,
+}
+
+/// Converts a smug project file, `name` is used if it doesn't specify the
+/// `session`.
+pub fn import(yaml: &str, name: &str) -> Result<Imported, InteropError> {
+    let config: Config = parse(FORMAT, yaml)?;
+    let mut warnings = vec![];
+    warn_unsupported(&mut warnings, "the session", &config.unsupported);
+
+    let project_root = config.root;
+    let windows = config
+        .windows
+        .into_iter()
+        .enumerate()
+        .map(|(idx, window)| convert_window(&mut warnings, project_root.as_deref(), idx, window))
+        .collect::<Vec<_>>();
+
+    let project = ProjectConfig {
+        project_name: config.session.unwrap_or_else(|| name.into()),
+        on_project_first_start: config.before_start.map(StringOrList::into_vec),
+        on_project_stop: config.stop.map(StringOrList::into_vec),
+        windows: (!windows.is_empty()).then_some(windows),
+        project_root,
+        ..Default::default()
+    };
+    Ok(Imported { project, warnings })
+}
+
+fn convert_window(
+    warnings: &mut Vec<String>,
+    project_root: Option<&str>,
+    idx: usize,
+    window: SmugWindow,
+) -> Window {
+    let name = window.name.unwrap_or_else(|| idx.to_string());
+    let section = format!("window {name:?}");
+    warn_unsupported(warnings, &section, &window.unsupported);
+    if window.manual {
+        warnings.push(format!(
+            "The manual {section} isn't supported, it's created with the others"
+        ));
+    }
+
+    let root = window.root.map(|root| join_root(project_root, &root));
+    let mut panes = vec![window.commands.map(StringOrList::into_vec)];
+    for (pane_idx, pane) in window.panes.into_iter().enumerate() {
+        // The first pane is the window's, with its commands
+        let pane_section = format!("pane {} of {section}", pane_idx + 1);
+        warn_unsupported(warnings, &pane_section, &pane.unsupported);
+        if pane.split_type.is_some() {
+            warnings.push(format!(
+                "Skipping the split type of {pane_section}, the window's layout is used"
+            ));
+        }
+
+        let mut commands = vec![];
+        if let Some(dir) = pane.root {
+            let parent = root.as_deref().or(project_root);
+            commands.push(cd_command(&join_root(parent, &dir)));
+        }
+        commands.extend(
+            pane.commands
+                .map(StringOrList::into_vec)
+                .unwrap_or_default(),
+        );
+        panes.push((!commands.is_empty()).then_some(commands));
+    }
+
+    Window {
+        layout: window.layout,
+        name,
+        panes,
+        options: None,
+        root,
+        log: None,
+        link: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn import_config() {
+        let yaml = r#"
+session: demo
+root: ~/src/demo
+env:
+  FOO: bar
+before_start:
+  - docker compose up -d
+stop: docker compose stop
+windows:
+  - name: code
+    root: app
+    layout: main-vertical
+    commands:
+      - vim
+    panes:
+      - type: horizontal
+        root: docs
+        commands:
+          - make html
+  - name: logs
+    manual: true
+"#;
+        let imported = import(yaml, "unused").unwrap();
+        let project = imported.project;
+        assert_eq!(project.project_name, "demo");
+        assert_eq!(project.project_root, Some("~/src/demo".into()));
+        assert_eq!(
+            project.on_project_first_start,
+            Some(vec!["docker compose up -d".into()])
+        );
+        assert_eq!(
+            project.on_project_stop,
+            Some(vec!["docker compose stop".into()])
+        );
+
+        let windows = project.windows.unwrap();
+        assert_eq!(windows[0].root, Some("~/src/demo/app".into()));
+        assert_eq!(windows[0].layout(), "main-vertical");
+        assert_eq!(
+            windows[0].panes,
+            vec![
+                Some(vec!["vim".into()]),
+                Some(vec!["cd ~/src/demo/app/docs".into(), "make html".into()]),
+            ]
+        );
+        assert_eq!(windows[1].panes, vec![None]);
+
+        let warnings = imported.warnings;
+        assert_eq!(warnings.len(), 3, "{warnings:?}");
+        for expected in ["\"env\"", "split type of pane 1", "manual window \"logs\""] {
+            assert!(
+                warnings.iter().any(|w| w.contains(expected)),
+                "{warnings:?}"
+            );
+        }
+    }
+}
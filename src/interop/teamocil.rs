@@ -0,0 +1,182 @@
+//! Imports [teamocil](https://github.com/remi/teamocil) layout files.
+//!
+//! For example:
+//!
+//! ```yaml
+//! name: demo
+//! windows:
+//!   - name: editor
+//!     root: ~/src/demo
+//!     layout: main-vertical
+//!     panes:
+//!       - vim
+//!       - commands:
+//!           - git pull
+//!           - git status
+//!         focus: true
+//! ```
+use super::{
+    parse, warn_focus, warn_unsupported, window_options, Imported, InteropError, StringOrList,
+};
+use crate::project_config::{ProjectConfig, Window};
+use serde::Deserialize;
+use serde_yaml::Value;
+use std::collections::BTreeMap;
+
+const FORMAT: &str = "teamocil";
+
+#[derive(Debug, Deserialize)]
+struct Layout {
+    name: Option<String>,
+    #[serde(default)]
+    windows: Vec<TeamocilWindow>,
+    #[serde(flatten)]
+    unsupported: BTreeMap<String, Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TeamocilWindow {
+    name: Option<String>,
+    root: Option<String>,
+    layout: Option<String>,
+    #[serde(default)]
+    options: BTreeMap<String, Value>,
+    #[serde(default)]
+    focus: bool,
+    #[serde(default)]
+    panes: Vec<Option<Pane>>,
+    #[serde(flatten)]
+    unsupported: BTreeMap<String, Value>,
+}
+
+/// A pane, either just its command or its settings.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Pane {
+    Command(String),
+    Settings {
+        commands: Option<StringOrList>,
+        #[serde(default)]
+        focus: bool,
+        #[serde(flatten)]
+        unsupported: BTreeMap<String, Value>,
+    },
+}
+
+/// Converts a teamocil layout file, `name` is used if it doesn't specify the
+/// session's `name`.
+pub fn import(yaml: &str, name: &str) -> Result<Imported, InteropError> {
+    let layout: Layout = parse(FORMAT, yaml)?;
+    let mut warnings = vec![];
+    warn_unsupported(&mut warnings, "the session", &layout.unsupported);
+
+    let windows = layout
+        .windows
+        .into_iter()
+        .enumerate()
+        .map(|(idx, window)| convert_window(&mut warnings, idx, window))
+        .collect::<Vec<_>>();
+
+    let project = ProjectConfig {
+        project_name: layout.name.unwrap_or_else(|| name.into()),
+        windows: (!windows.is_empty()).then_some(windows),
+        ..Default::default()
+    };
+    Ok(Imported { project, warnings })
+}
+
+fn convert_window(warnings: &mut Vec<String>, idx: usize, window: TeamocilWindow) -> Window {
+    let name = window.name.unwrap_or_else(|| idx.to_string());
+    let section = format!("window {name:?}");
+    warn_unsupported(warnings, &section, &window.unsupported);
+    if window.focus {
+        warn_focus(warnings, &section, idx);
+    }
+
+    let mut panes: Vec<_> = window
+        .panes
+        .into_iter()
+        .enumerate()
+        .map(|(pane_idx, pane)| match pane {
+            None => None,
+            Some(Pane::Command(command)) => Some(vec![command]),
+            Some(Pane::Settings {
+                commands,
+                focus,
+                unsupported,
+            }) => {
+                let pane_section = format!("pane {pane_idx} of {section}");
+                warn_unsupported(warnings, &pane_section, &unsupported);
+                if focus {
+                    warn_focus(warnings, &pane_section, pane_idx);
+                }
+                commands.map(StringOrList::into_vec)
+            }
+        })
+        .collect();
+    if panes.is_empty() {
+        panes.push(None);
+    }
+
+    Window {
+        options: window_options(warnings, &name, window.options),
+        layout: window.layout,
+        name,
+        panes,
+        root: window.root,
+        log: None,
+        link: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn import_layout() {
+        let yaml = r#"
+name: demo
+windows:
+  - name: editor
+    root: ~/src/demo
+    layout: main-vertical
+    clear: true
+    panes:
+      - vim
+      - commands:
+          - git pull
+          - git status
+        focus: true
+  - name: server
+    focus: true
+    panes:
+      - commands: rails server
+"#;
+        let imported = import(yaml, "unused").unwrap();
+        let project = imported.project;
+        assert_eq!(project.project_name, "demo");
+
+        let windows = project.windows.unwrap();
+        assert_eq!(windows[0].name, "editor");
+        assert_eq!(windows[0].root, Some("~/src/demo".into()));
+        assert_eq!(windows[0].layout(), "main-vertical");
+        assert_eq!(
+            windows[0].panes,
+            vec![
+                Some(vec!["vim".into()]),
+                Some(vec!["git pull".into(), "git status".into()]),
+            ]
+        );
+        assert_eq!(windows[1].panes, vec![Some(vec!["rails server".into()])]);
+
+        let warnings = imported.warnings;
+        assert_eq!(warnings.len(), 3, "{warnings:?}");
+        for expected in ["\"clear\"", "Focus on pane 1", "Focus on window \"server\""] {
+            assert!(
+                warnings.iter().any(|w| w.contains(expected)),
+                "{warnings:?}"
+            );
+        }
+    }
+}
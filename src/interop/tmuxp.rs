@@ -0,0 +1,460 @@
+//! Imports [tmuxp](https://tmuxp.git-pull.com) session files.
+//!
+//! For example:
+//!
+//! ```yaml
+//! session_name: demo
+//! start_directory: ~/src/demo
+//! before_script: ./bootstrap.sh
+//! shell_command_before: workon demo
+//! windows:
+//!   - window_name: editor
+//!     layout: main-vertical
+//!     options:
+//!       main-pane-width: 60%
+//!     panes:
+//!       - shell_command: vim
+//!       - start_directory: docs
+//!         shell_command:
+//!           - make html
+//! ```
+//!
+//! `before_script` is run as `on_project_first_start`, the session's
+//! `shell_command_before` as `pre_window`, and its `environment` is the
+//! project's `env`. A pane's `start_directory` and the
+//! `shell_command_before` of a window or pane are prepended to the pane's
+//! commands. `focus` isn't supported, the first window and pane are selected.
+//!
+//! Exporting does the reverse, a single `on_project_first_start` or
+//! `on_project_start` command is exported as the `before_script` (which
+//! isn't run with a shell), other hooks are skipped.
+use super::{
+    cd_command, is_false, join_root, parse, warn_focus, warn_unexported, warn_unsupported,
+    window_options, Exported, Imported, InteropError,
+};
+use crate::project_config::{ProjectConfig, Window};
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+use std::collections::BTreeMap;
+
+const FORMAT: &str = "tmuxp";
+
+#[derive(Debug, Deserialize, Serialize)]
+struct Session {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    session_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_directory: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    before_script: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    shell_command_before: Option<ShellCommands>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    environment: Option<BTreeMap<String, String>>,
+    #[serde(default)]
+    windows: Vec<TmuxpWindow>,
+    #[serde(flatten)]
+    unsupported: BTreeMap<String, Value>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct TmuxpWindow {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    window_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    layout: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_directory: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    shell_command_before: Option<ShellCommands>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    options: BTreeMap<String, Value>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    focus: bool,
+    #[serde(default)]
+    panes: Vec<Option<Pane>>,
+    #[serde(flatten)]
+    unsupported: BTreeMap<String, Value>,
+}
+
+/// A pane, either just its command(s) or its settings.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+enum Pane {
+    Commands(ShellCommands),
+    Settings(PaneSettings),
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct PaneSettings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    shell_command: Option<ShellCommands>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    shell_command_before: Option<ShellCommands>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_directory: Option<String>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    focus: bool,
+    #[serde(flatten)]
+    unsupported: BTreeMap<String, Value>,
+}
+
+/// A single command or a list of commands.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+enum ShellCommands {
+    Single(ShellCommand),
+    List(Vec<ShellCommand>),
+}
+
+/// A command, either just the command or the command with its settings
+/// (e.g. `enter: false`).
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+enum ShellCommand {
+    Command(String),
+    Settings {
+        cmd: String,
+        #[serde(flatten)]
+        unsupported: BTreeMap<String, Value>,
+    },
+}
+
+impl ShellCommands {
+    /// Returns the commands, adding warnings for their unsupported settings.
+    fn into_vec(self, warnings: &mut Vec<String>, section: &str) -> Vec<String> {
+        let commands = match self {
+            ShellCommands::Single(command) => vec![command],
+            ShellCommands::List(commands) => commands,
+        };
+        commands
+            .into_iter()
+            .map(|command| match command {
+                ShellCommand::Command(cmd) => cmd,
+                ShellCommand::Settings { cmd, unsupported } => {
+                    warn_unsupported(
+                        warnings,
+                        &format!("command {cmd:?} in {section}"),
+                        &unsupported,
+                    );
+                    cmd
+                }
+            })
+            .collect()
+    }
+}
+
+/// Converts a tmuxp session file, `name` is used if it doesn't specify the
+/// `session_name`.
+pub fn import(yaml: &str, name: &str) -> Result<Imported, InteropError> {
+    let session: Session = parse(FORMAT, yaml)?;
+    let mut warnings = vec![];
+    warn_unsupported(&mut warnings, "the session", &session.unsupported);
+
+    let project_root = session.start_directory;
+    let pre_window = session
+        .shell_command_before
+        .map(|commands| commands.into_vec(&mut warnings, "the session"));
+
+    let windows = session
+        .windows
+        .into_iter()
+        .enumerate()
+        .map(|(idx, window)| convert_window(&mut warnings, project_root.as_deref(), idx, window))
+        .collect::<Vec<_>>();
+
+    let project = ProjectConfig {
+        project_name: session.session_name.unwrap_or_else(|| name.into()),
+        on_project_first_start: session.before_script.map(|script| vec![script]),
+        pre_window,
+        env: session.environment,
+        windows: (!windows.is_empty()).then_some(windows),
+        project_root,
+        ..Default::default()
+    };
+    Ok(Imported { project, warnings })
+}
+
+fn convert_window(
+    warnings: &mut Vec<String>,
+    project_root: Option<&str>,
+    idx: usize,
+    window: TmuxpWindow,
+) -> Window {
+    let name = window.window_name.unwrap_or_else(|| idx.to_string());
+    let section = format!("window {name:?}");
+    warn_unsupported(warnings, &section, &window.unsupported);
+    if window.focus {
+        warn_focus(warnings, &section, idx);
+    }
+
+    let root = window
+        .start_directory
+        .map(|root| join_root(project_root, &root));
+    let before = window
+        .shell_command_before
+        .map(|commands| commands.into_vec(warnings, &section))
+        .unwrap_or_default();
+
+    let mut panes: Vec<_> = window
+        .panes
+        .into_iter()
+        .enumerate()
+        .map(|(pane_idx, pane)| {
+            let pane_section = format!("pane {pane_idx} of {section}");
+            let mut commands = vec![];
+            match pane {
+                None => commands.extend(before.iter().cloned()),
+                Some(Pane::Commands(shell_commands)) => {
+                    commands.extend(before.iter().cloned());
+                    commands.extend(shell_commands.into_vec(warnings, &pane_section));
+                }
+                Some(Pane::Settings(settings)) => {
+                    warn_unsupported(warnings, &pane_section, &settings.unsupported);
+                    if settings.focus {
+                        warn_focus(warnings, &pane_section, pane_idx);
+                    }
+                    if let Some(dir) = settings.start_directory {
+                        let parent = root.as_deref().or(project_root);
+                        commands.push(cd_command(&join_root(parent, &dir)));
+                    }
+                    commands.extend(before.iter().cloned());
+                    for shell_commands in [settings.shell_command_before, settings.shell_command]
+                        .into_iter()
+                        .flatten()
+                    {
+                        commands.extend(shell_commands.into_vec(warnings, &pane_section));
+                    }
+                }
+            }
+            (!commands.is_empty()).then_some(commands)
+        })
+        .collect();
+    if panes.is_empty() {
+        panes.push((!before.is_empty()).then_some(before));
+    }
+
+    Window {
+        options: window_options(warnings, &name, window.options),
+        layout: window.layout,
+        name,
+        panes,
+        root,
+        log: None,
+        link: None,
+    }
+}
+
+/// Converts a project to a tmuxp session file.
+pub fn export(project: &ProjectConfig) -> Result<Exported, InteropError> {
+    let mut warnings = vec![];
+    let mut before_script = None;
+    let start_commands: Vec<_> = [&project.on_project_first_start, &project.on_project_start]
+        .into_iter()
+        .flatten()
+        .flatten()
+        .collect();
+    match start_commands.as_slice() {
+        [] => {}
+        [command] => before_script = Some(command.to_string()),
+        _ => warnings.push(
+            "Skipping on_project_first_start and on_project_start, tmuxp supports a single before_script"
+                .into(),
+        ),
+    }
+    warn_unexported(&mut warnings, project, FORMAT);
+
+    let windows = project
+        .windows
+        .iter()
+        .flatten()
+        .map(|window| TmuxpWindow {
+            window_name: Some(window.name.clone()),
+            layout: Some(window.layout().into()),
+            start_directory: window.root.clone(),
+            shell_command_before: None,
+            options: window
+                .options
+                .iter()
+                .flatten()
+                .map(|(name, value)| (name.clone(), Value::String(value.clone())))
+                .collect(),
+            focus: false,
+            panes: window
+                .panes
+                .iter()
+                .map(|commands| {
+                    let commands = commands.iter().flatten().cloned();
+                    Some(Pane::Settings(PaneSettings {
+                        shell_command: Some(ShellCommands::List(
+                            commands.map(ShellCommand::Command).collect(),
+                        )),
+                        shell_command_before: None,
+                        start_directory: None,
+                        focus: false,
+                        unsupported: BTreeMap::new(),
+                    }))
+                })
+                .collect(),
+            unsupported: BTreeMap::new(),
+        })
+        .collect();
+
+    let session = Session {
+        session_name: Some(project.project_name.clone()),
+        start_directory: project.project_root.clone(),
+        before_script,
+        shell_command_before: project.pre_window.as_ref().map(|commands| {
+            ShellCommands::List(
+                commands
+                    .iter()
+                    .cloned()
+                    .map(ShellCommand::Command)
+                    .collect(),
+            )
+        }),
+        environment: project.env.clone(),
+        windows,
+        unsupported: BTreeMap::new(),
+    };
+    let content = serde_yaml::to_string(&session).map_err(InteropError::Serialize)?;
+    Ok(Exported { content, warnings })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn import_session() {
+        let yaml = r#"
+session_name: demo
+start_directory: ~/src/demo
+before_script: ./bootstrap.sh
+shell_command_before: workon demo
+global_options:
+  default-shell: /bin/zsh
+windows:
+  - window_name: editor
+    layout: main-vertical
+    options:
+      main-pane-width: 60%
+      synchronize-panes: false
+    panes:
+      - shell_command: vim
+      - start_directory: docs
+        focus: true
+        shell_command:
+          - make html
+          - cmd: make serve
+            enter: false
+      -
+  - window_name: logs
+    start_directory: logs
+    shell_command_before:
+      - cd current
+    panes:
+      - tail -f app.log
+"#;
+        let imported = import(yaml, "unused").unwrap();
+        let project = imported.project;
+        assert_eq!(project.project_name, "demo");
+        assert_eq!(project.project_root, Some("~/src/demo".into()));
+        assert_eq!(
+            project.on_project_first_start,
+            Some(vec!["./bootstrap.sh".into()])
+        );
+        assert_eq!(project.pre_window, Some(vec!["workon demo".into()]));
+
+        let windows = project.windows.unwrap();
+        assert_eq!(windows[0].name, "editor");
+        assert_eq!(windows[0].layout(), "main-vertical");
+        assert_eq!(windows[0].root, None);
+        let options = windows[0].options.as_ref().unwrap();
+        assert_eq!(options["main-pane-width"], "60%");
+        assert_eq!(options["synchronize-panes"], "off");
+        assert_eq!(
+            windows[0].panes,
+            vec![
+                Some(vec!["vim".into()]),
+                Some(vec![
+                    "cd ~/src/demo/docs".into(),
+                    "make html".into(),
+                    "make serve".into()
+                ]),
+                None,
+            ]
+        );
+
+        assert_eq!(windows[1].root, Some("~/src/demo/logs".into()));
+        assert_eq!(windows[1].layout(), "tiled");
+        assert_eq!(
+            windows[1].panes,
+            vec![Some(vec!["cd current".into(), "tail -f app.log".into()])]
+        );
+
+        let warnings = imported.warnings;
+        assert_eq!(warnings.len(), 3, "{warnings:?}");
+        for expected in ["global_options", "\"enter\"", "Focus on pane 1"] {
+            assert!(
+                warnings.iter().any(|w| w.contains(expected)),
+                "{warnings:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn import_without_panes() {
+        let yaml = "windows:\n  - window_name: shell\n";
+        let project = import(yaml, "fallback").unwrap().project;
+        assert_eq!(project.project_name, "fallback");
+        assert_eq!(project.windows.unwrap()[0].panes, vec![None]);
+    }
+
+    #[test]
+    fn invalid_file() {
+        assert!(import("windows: 3", "demo").is_err());
+    }
+
+    #[test]
+    fn export_roundtrip() {
+        let yaml = r#"
+project_name: demo
+project_root: ~/src/demo
+on_project_first_start: ./bootstrap.sh
+on_project_stop: docker compose stop
+pre_window: workon demo
+env:
+  RAILS_ENV: development
+windows:
+  - editor:
+      layout: main-vertical
+      root: ~/src/demo/app
+      options:
+        main-pane-width: 60%
+      panes:
+        - vim
+        - - make html
+          - make serve
+        -
+  - logs:
+      layout: tiled
+      panes:
+        - tail -f app.log
+"#;
+        let project = ProjectConfig::try_from(yaml.to_string()).unwrap();
+        let exported = export(&project).unwrap();
+        assert_eq!(exported.warnings.len(), 1, "{:?}", exported.warnings);
+        assert!(exported.warnings[0].contains("on_project_stop"));
+
+        let imported = import(&exported.content, "unused").unwrap();
+        assert!(imported.warnings.is_empty(), "{:?}", imported.warnings);
+        assert_eq!(
+            imported.project,
+            ProjectConfig {
+                on_project_stop: None,
+                ..project
+            }
+        );
+    }
+}
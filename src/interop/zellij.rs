@@ -0,0 +1,231 @@
+//! Exports projects to [Zellij](https://zellij.dev) KDL layouts.
+//!
+//! Windows become tabs (with the window's root as the tab's `cwd`). A pane's
+//! commands, following the project's `pre_window`, are run with `sh -c`
+//! and then replaced with the user's shell, so the pane stays open like in
+//! tmux. Shell functions and aliases (e.g. `workon`) aren't available to
+//! `sh`.
+//!
+//! tmux layouts are approximated with nested panes:
+//!
+//! * `even-horizontal` - the panes side by side.
+//! * `even-vertical` - the panes stacked.
+//! * `main-vertical` - the first pane on the left (sized by the
+//!   `main-pane-width` option), and the rest stacked on the right.
+//! * `main-horizontal` - the first pane on top (sized by the
+//!   `main-pane-height` option), and the rest side by side below it.
+//! * `tiled` and custom layouts - a grid of rows.
+use super::{warn_unexported, Exported, InteropError};
+use crate::project_config::{ProjectConfig, Window};
+use std::fmt::Write;
+
+const FORMAT: &str = "zellij";
+
+/// A pane or a split containing other panes.
+#[derive(Debug)]
+enum Node {
+    Pane {
+        commands: Vec<String>,
+        size: Option<String>,
+    },
+    Split {
+        /// Zellij's `split_direction`, `vertical` places the panes side by
+        /// side.
+        vertical: bool,
+        children: Vec<Node>,
+    },
+}
+
+impl Node {
+    /// Returns the split of the nodes, or the node itself if it's the only one.
+    fn split(vertical: bool, mut children: Vec<Node>) -> Node {
+        if children.len() == 1 {
+            children.remove(0)
+        } else {
+            Node::Split { vertical, children }
+        }
+    }
+
+    /// Sets the size of a pane.
+    fn with_size(mut self, new_size: Option<String>) -> Node {
+        if let Node::Pane { size, .. } = &mut self {
+            *size = new_size;
+        }
+        self
+    }
+}
+
+/// Converts a project to a Zellij layout.
+pub fn export(project: &ProjectConfig) -> Result<Exported, InteropError> {
+    let mut warnings = vec![];
+    let hooks = [
+        ("on_project_start", &project.on_project_start),
+        ("on_project_first_start", &project.on_project_first_start),
+    ];
+    for (name, hook) in hooks {
+        if hook.is_some() {
+            warnings.push(format!("Skipping {name}, {FORMAT} has no equivalent"));
+        }
+    }
+    if project.env.is_some() {
+        warnings.push(format!("Skipping env, {FORMAT} has no equivalent"));
+    }
+    warn_unexported(&mut warnings, project, FORMAT);
+
+    let mut content = String::from("layout {\n");
+    if let Some(root) = &project.project_root {
+        writeln!(content, "    cwd {}", quote(&shellexpand::tilde(root))).unwrap();
+    }
+    for (idx, window) in project.windows.iter().flatten().enumerate() {
+        let mut attributes = format!("name={}", quote(&window.name));
+        if let Some(root) = &window.root {
+            write!(attributes, " cwd={}", quote(&shellexpand::tilde(root))).unwrap();
+        }
+        if idx == 0 {
+            attributes.push_str(" focus=true");
+        }
+        writeln!(content, "    tab {attributes} {{").unwrap();
+        let node = window_node(&mut warnings, window, project.pre_window.as_deref());
+        write_node(&mut content, &node, 2);
+        content.push_str("    }\n");
+    }
+    content.push_str("}\n");
+    Ok(Exported { content, warnings })
+}
+
+/// Returns the nodes of the window's panes, arranged by its layout.
+fn window_node(warnings: &mut Vec<String>, window: &Window, pre_window: Option<&[String]>) -> Node {
+    let mut panes: Vec<_> = window
+        .panes
+        .iter()
+        .map(|commands| Node::Pane {
+            commands: pre_window
+                .into_iter()
+                .flatten()
+                .chain(commands.iter().flatten())
+                .cloned()
+                .collect(),
+            size: None,
+        })
+        .collect();
+    if panes.len() == 1 {
+        return panes.remove(0);
+    }
+
+    let option = |name: &str| {
+        window
+            .options
+            .as_ref()
+            .and_then(|options| options.get(name))
+            .map(|size| size.trim().to_string())
+    };
+    match window.layout() {
+        "even-horizontal" => Node::split(true, panes),
+        "even-vertical" => Node::split(false, panes),
+        "main-vertical" => {
+            let rest = panes.split_off(1);
+            let main = panes.remove(0).with_size(option("main-pane-width"));
+            Node::split(true, vec![main, Node::split(false, rest)])
+        }
+        "main-horizontal" => {
+            let rest = panes.split_off(1);
+            let main = panes.remove(0).with_size(option("main-pane-height"));
+            Node::split(false, vec![main, Node::split(true, rest)])
+        }
+        layout => {
+            if layout != "tiled" {
+                warnings.push(format!(
+                    "Layout {layout:?} of window {:?} is approximated as tiled",
+                    window.name
+                ));
+            }
+            let columns = (panes.len() as f64).sqrt().ceil() as usize;
+            let mut rows = vec![];
+            while !panes.is_empty() {
+                let rest = panes.split_off(columns.min(panes.len()));
+                rows.push(Node::split(true, panes));
+                panes = rest;
+            }
+            Node::split(false, rows)
+        }
+    }
+}
+
+/// Writes the node as KDL, indented by `depth` levels.
+fn write_node(content: &mut String, node: &Node, depth: usize) {
+    let indent = "    ".repeat(depth);
+    match node {
+        Node::Pane { commands, size } => {
+            let size = size
+                .as_ref()
+                .map_or(String::new(), |size| format!(" size={}", quote(size)));
+            if commands.is_empty() {
+                writeln!(content, "{indent}pane{size}").unwrap();
+            } else {
+                let script = format!("{}\nexec \"${{SHELL:-sh}}\"", commands.join("\n"));
+                writeln!(content, "{indent}pane{size} command=\"sh\" {{").unwrap();
+                writeln!(content, "{indent}    args \"-c\" {}", quote(&script)).unwrap();
+                writeln!(content, "{indent}}}").unwrap();
+            }
+        }
+        Node::Split { vertical, children } => {
+            let direction = if *vertical { "vertical" } else { "horizontal" };
+            writeln!(content, "{indent}pane split_direction=\"{direction}\" {{").unwrap();
+            for child in children {
+                write_node(content, child, depth + 1);
+            }
+            writeln!(content, "{indent}}}").unwrap();
+        }
+    }
+}
+
+/// Quotes a KDL string, Rust's escaping of strings is compatible with KDL's.
+fn quote(value: &str) -> String {
+    format!("{value:?}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project_from(yaml: &str) -> ProjectConfig {
+        ProjectConfig::try_from(yaml.to_string()).unwrap()
+    }
+
+    #[test]
+    fn export_layout() {
+        let project = project_from(
+            r#"
+project_name: demo
+project_root: /home/dummy/demo
+on_project_start: docker compose up -d
+pre_window: source .env
+windows:
+  - editor:
+      layout: main-vertical
+      options:
+        main-pane-width: 60%
+      panes:
+        - vim
+        - echo "two"
+        -
+  - grid:
+      root: /tmp
+      layout: tiled
+      panes: [a, b, c]
+  - logs: tail -f app.log
+"#,
+        );
+        let exported = export(&project).unwrap();
+        insta::assert_snapshot!(exported.content);
+        assert_eq!(exported.warnings.len(), 1, "{:?}", exported.warnings);
+    }
+
+    #[test]
+    fn approximated_layouts() {
+        let yaml = "project_name: demo\nwindows:\n  - custom:\n      layout: 5e4b,80x24,0,0\n      panes: [a, b]";
+        let exported = export(&project_from(yaml)).unwrap();
+        assert!(exported.warnings[0].contains("approximated as tiled"));
+        assert!(exported.content.contains("split_direction=\"vertical\""));
+    }
+}
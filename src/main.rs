@@ -4,34 +4,174 @@ extern crate clap;
 
 mod app;
 mod error;
+mod interop;
 mod project_config;
 mod tmux;
 
 use app::actions;
-use app::actions::NewProjectFrom;
-use app::cli::{Cli, Commands};
-use clap::Parser;
-use error::AppErrorForDisplay;
+use app::actions::{AttachProjectOptions, ListOptions, NewProjectFrom};
+use app::cli::{Cli, Commands, TemplatesCommands};
+use app::config;
+use app::groups::group_name;
+use app::settings::Settings;
+use app::templates::DEFAULT_TEMPLATE;
+use app::trust::TrustStatus;
+use clap::{CommandFactory, Parser};
+use error::{AppError, AppErrorForDisplay};
 
 fn main() -> Result<(), AppErrorForDisplay> {
     let cli = Cli::parse();
+    if let Some(config_dir) = cli.config_dir {
+        config::set_config_dir(config_dir);
+    }
+
+    let result = Settings::init().and_then(|_| run_command(cli.commands));
+
+    if let Some(code) = result.as_ref().err().and_then(|e| e.exit_code()) {
+        eprintln!("Error: {}", result.unwrap_err());
+        std::process::exit(code);
+    }
+    result.map_err(|e| e.into())
+}
+
+/// Exits with the subcommand's help, like a subcommand with
+/// `arg_required_else_help` does when no arguments are given.
+fn exit_with_help(subcommand: &str) -> ! {
+    let mut cli = Cli::command();
+    cli.build();
+    let command = cli
+        .find_subcommand_mut(subcommand)
+        .expect("The subcommand exists");
+    eprint!("{}", command.render_help());
+    std::process::exit(2)
+}
 
-    match cli.commands {
-        Commands::Run { project } => actions::run_project(&project),
-        Commands::Stop { project } => actions::stop(&project),
-        Commands::Debug { project } => actions::debug_project(&project),
-        Commands::Edit { project } => actions::edit_project(&project),
-        Commands::Delete { project } => actions::delete_project(&project),
-        Commands::New { project, blank } => {
+/// Runs the command requested by the CLI.
+fn run_command(commands: Commands) -> Result<(), AppError> {
+    match commands {
+        Commands::Run {
+            project,
+            tag,
+            fail_fast,
+            run,
+        } => {
+            let options = (&run).into();
+            match (project.as_deref().and_then(group_name), tag.is_empty()) {
+                (Some(group), _) => actions::run_group(group, &options, fail_fast),
+                (None, true) => actions::run_project(project.as_deref(), &options),
+                (None, false) => actions::run_tagged(&tag, &options, fail_fast),
+            }
+        }
+        Commands::Open { dir, run } => actions::open_directory(&dir, &(&run).into()),
+        Commands::Restart {
+            project,
+            window,
+            trust,
+        } => actions::restart_project(&project, window.as_deref(), trust),
+        Commands::Send {
+            project,
+            target,
+            send,
+            keys,
+        } => actions::send_keys(&project, &target, &keys, &send),
+        Commands::Exec {
+            project,
+            window,
+            send,
+            command,
+        } => actions::exec_command(&project, window.as_deref(), &command.join(" "), &send),
+        Commands::Logs {
+            project,
+            target,
+            lines,
+            follow,
+        } => actions::pane_logs(&project, &target, lines, follow),
+        Commands::Stop {
+            project,
+            tag,
+            fail_fast,
+            trust,
+        } => match (project.as_deref().and_then(group_name), tag.is_empty()) {
+            (Some(group), _) => actions::stop_group(group, trust, fail_fast),
+            (None, true) => actions::stop(project.as_deref(), trust),
+            (None, false) => actions::stop_tagged(&tag, trust, fail_fast),
+        },
+        Commands::Status { project } => actions::project_status(project.as_deref()),
+        Commands::Attach {
+            project,
+            view,
+            window,
+            pane,
+            client,
+            read_only,
+            detach_others,
+        } => {
+            let options = AttachProjectOptions {
+                view,
+                window: window.as_deref(),
+                pane,
+                client: client.as_deref(),
+                read_only,
+                detach_others,
+            };
+            actions::attach_project(project.as_deref(), &options)
+        }
+        Commands::Switch { next, previous } => actions::switch_session(next, previous),
+        Commands::Debug {
+            project,
+            format,
+            tmux,
+        } => actions::debug_project(project.as_deref(), format, &tmux),
+        Commands::Edit { project } => actions::edit_project(project.as_deref()),
+        Commands::Delete { project } => {
+            if project.is_none() && !actions::can_prompt() {
+                exit_with_help("delete");
+            }
+            actions::delete_project(project.as_deref())
+        }
+        Commands::New {
+            project,
+            blank,
+            template,
+            vars,
+        } => {
             let new_project = match blank {
                 true => NewProjectFrom::Blank { name: &project },
-                false => NewProjectFrom::DefaultTemplate { name: &project },
+                false => NewProjectFrom::Template {
+                    name: &project,
+                    template: template.as_deref().unwrap_or(DEFAULT_TEMPLATE),
+                    vars: &vars,
+                },
             };
             actions::new_project(&new_project)
         }
-        Commands::List => actions::list_projects(),
-        Commands::Copy { existing, new } => actions::copy_project(&existing, &new),
+        Commands::Templates { command } => match command {
+            TemplatesCommands::List => actions::list_templates(),
+            TemplatesCommands::Show { name } => actions::show_template(&name),
+            TemplatesCommands::Edit { name } => actions::edit_template(&name),
+        },
+        Commands::List { tag, long, json } => actions::list_projects(&ListOptions {
+            tags: &tag,
+            long,
+            json,
+        }),
+        Commands::Copy {
+            existing,
+            new,
+            root,
+        } => actions::copy_project(&existing, &new, root.as_deref()),
+        Commands::Rename {
+            existing,
+            new,
+            root,
+        } => actions::rename_project(&existing, &new, root.as_deref()),
+        Commands::Import { from, file, name } => {
+            actions::import_project(from, &file, name.as_deref())
+        }
+        Commands::Export { to, project } => actions::export_project(&project, to),
+        Commands::Allow { project } => actions::set_trust(project.as_deref(), TrustStatus::Allowed),
+        Commands::Deny { project } => actions::set_trust(project.as_deref(), TrustStatus::Denied),
+        Commands::Trusted => actions::list_trusted(),
         Commands::Doctor => actions::check_config(),
     }
-    .map_err(|e| e.into())
 }
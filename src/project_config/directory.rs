@@ -0,0 +1,140 @@
+//! Generates a project for a directory without a project file, e.g.
+//! `rusmux run .`.
+use super::project::ProjectConfig;
+use super::window::Window;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Settings for the projects generated for directories, the `directory`
+/// section of the global settings file. For example:
+///
+/// ```yaml
+/// directory:
+///   windows:
+///     - editor: ${EDITOR:-vi}
+///     - shell:
+///     - git: git status
+///   detect_window: shell
+///   detect:
+///     Cargo.toml: cargo watch -x check
+///     package.json: npm run dev
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct DirectorySettings {
+    /// The windows of the project, same as a project file's windows.
+    pub windows: Vec<Window>,
+    /// Files to detect in the directory, with the command of the pane added
+    /// for each of the existing ones.
+    pub detect: BTreeMap<String, String>,
+    /// The window the detected panes are added to, created if it isn't
+    /// one of the `windows`.
+    pub detect_window: String,
+}
+
+impl Default for DirectorySettings {
+    fn default() -> Self {
+        let window = |name: &str, command: Option<&str>| Window {
+            name: name.into(),
+            layout: None,
+            panes: vec![command.map(|command| vec![command.into()])],
+            options: None,
+            root: None,
+            log: None,
+            link: None,
+        };
+        Self {
+            windows: vec![
+                window("editor", Some("${EDITOR:-vi}")),
+                window("shell", None),
+                window("git", Some("git status")),
+            ],
+            detect: BTreeMap::from([
+                ("Cargo.toml".into(), "cargo watch -x check".into()),
+                ("package.json".into(), "npm run dev".into()),
+            ]),
+            detect_window: "shell".into(),
+        }
+    }
+}
+
+impl DirectorySettings {
+    /// Returns the project of the directory, named after it and rooted in it.
+    ///
+    /// The session's name can't contain `.` or `:` (which tmux uses for
+    /// targets), so these are replaced with `_`.
+    pub fn project(self, dir: &Path) -> ProjectConfig {
+        let name = dir.file_name().map_or("root".into(), |name| {
+            name.to_string_lossy().replace(['.', ':'], "_")
+        });
+
+        let mut windows = self.windows;
+        let detected: Vec<_> = self
+            .detect
+            .into_iter()
+            .filter(|(file, _)| dir.join(file).exists())
+            .map(|(_, command)| Some(vec![command]))
+            .collect();
+        if !detected.is_empty() {
+            match windows.iter_mut().find(|w| w.name == self.detect_window) {
+                Some(window) => window.panes.extend(detected),
+                None => windows.push(Window {
+                    name: self.detect_window,
+                    layout: None,
+                    panes: detected,
+                    options: None,
+                    root: None,
+                    log: None,
+                    link: None,
+                }),
+            }
+        }
+
+        ProjectConfig {
+            project_name: name,
+            project_root: Some(dir.to_string_lossy().into_owned()),
+            windows: (!windows.is_empty()).then_some(windows),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{create_dir_all, remove_dir_all, File};
+
+    #[test]
+    fn directory_project() {
+        let dir = std::env::temp_dir().join(format!("rusmux-test.{}", std::process::id()));
+        create_dir_all(&dir).unwrap();
+        File::create(dir.join("Cargo.toml")).unwrap();
+
+        let project = DirectorySettings::default().project(&dir);
+        remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            project.project_name,
+            format!("rusmux-test_{}", std::process::id())
+        );
+        assert_eq!(project.project_root, Some(dir.to_string_lossy().into()));
+        let windows = project.windows.unwrap();
+        assert_eq!(windows.len(), 3);
+        assert_eq!(
+            windows[1].panes,
+            vec![None, Some(vec!["cargo watch -x check".into()])]
+        );
+    }
+
+    #[test]
+    fn detect_window_created() {
+        let yaml = "windows: []\ndetect_window: watch\ndetect:\n  .: echo detected";
+        let settings: DirectorySettings = serde_yaml::from_str(yaml).unwrap();
+        let project = settings.project(Path::new("/tmp"));
+        let windows = project.windows.unwrap();
+        assert_eq!(windows.len(), 1);
+        assert_eq!(windows[0].name, "watch");
+        assert_eq!(windows[0].panes, vec![Some(vec!["echo detected".into()])]);
+    }
+}
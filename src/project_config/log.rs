@@ -0,0 +1,58 @@
+//! Handles the optional `log` setting of a project or a window.
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Pipes the output of the panes to per pane files (with `tmux pipe-pane`)
+/// once they're created. Can be set for the project, and overridden per
+/// window. For example:
+///
+/// ```yaml
+/// log: true # log to the default directory
+/// log: ~/logs/demo # log to a specific directory
+/// log: false # don't log (e.g. for a single window)
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum LogSetting {
+    /// Enable or disable logging to the default directory.
+    Enabled(bool),
+    /// Log to the specified directory.
+    Dir(String),
+}
+
+impl LogSetting {
+    /// Returns the directory for the log files, `None` if logging is
+    /// disabled.
+    ///
+    /// The default directory is `logs` under the user's local data directory
+    /// (e.g. `~/.local/share/rusmux/logs`).
+    pub fn dir(&self) -> Option<PathBuf> {
+        match self {
+            LogSetting::Enabled(false) => None,
+            LogSetting::Enabled(true) => ProjectDirs::from("org", crate_authors!(), crate_name!())
+                .map(|dirs| dirs.data_local_dir().join("logs")),
+            LogSetting::Dir(dir) => Some(PathBuf::from(shellexpand::tilde(dir).as_ref())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LogSetting;
+    use std::path::PathBuf;
+
+    #[test]
+    fn log_setting() {
+        let enabled: LogSetting = serde_yaml::from_str("true").unwrap();
+        assert_eq!(enabled, LogSetting::Enabled(true));
+        assert!(enabled.dir().is_some());
+
+        let disabled: LogSetting = serde_yaml::from_str("false").unwrap();
+        assert_eq!(disabled, LogSetting::Enabled(false));
+        assert_eq!(disabled.dir(), None);
+
+        let dir: LogSetting = serde_yaml::from_str("/var/log/demo").unwrap();
+        assert_eq!(dir.dir(), Some(PathBuf::from("/var/log/demo")));
+    }
+}
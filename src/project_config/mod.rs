@@ -11,11 +11,16 @@
 //! A project must include the name, optional events (like `start`, `stop`),
 //! and optional windows, each with optional panes.
 
+pub mod directory;
 pub mod error;
+pub mod log;
 pub mod project;
-mod stringorvec;
+pub mod rewrite;
+pub mod session;
+pub(crate) mod stringorvec;
 pub mod window;
 
 pub use error::ProjectParseError;
-pub use project::ProjectConfig;
+pub use project::{ProjectConfig, ProjectDefaults};
+pub use rewrite::ProjectRewrite;
 pub use window::Window;
@@ -1,9 +1,12 @@
 //! The project configuration.
 use super::error::ProjectParseError;
+use super::log::LogSetting;
+use super::session::Session;
 use super::stringorvec;
 use super::window::Window;
 use serde::{Deserialize, Serialize};
 use serde_yaml::Value;
+use std::collections::BTreeMap;
 use std::convert::TryFrom;
 
 /// The `.yml` file is de-serialized to this struct. For example;
@@ -32,40 +35,215 @@ use std::convert::TryFrom;
 ///
 /// - [String] containing the yaml.
 /// - [Value] for the deserialized yaml from a string.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ProjectConfig {
     /// Required project name
     #[serde(alias = "name")]
     pub project_name: String,
     /// The root directory for the project (optional).
     /// Will be shell expanded and `cd` into before starting the session.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub project_root: Option<String>,
+    /// A short description of the project (optional), shown by the project
+    /// picker.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Tags of the project (optional), for filtering the projects listed or
+    /// run together. Can be a single tag (string), or several tags (list of
+    /// strings).
+    #[serde(default)]
+    #[serde(deserialize_with = "stringorvec::deserialize_optional_vec_or_string")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
     /// Optional Command(s) to run upon session start, before starting
     /// the tmux session. Can be a single command (string), or several commands
     /// (list of strings).
     #[serde(default)]
     #[serde(deserialize_with = "stringorvec::deserialize_optional_vec_or_string")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub on_project_start: Option<Vec<String>>,
-    /// Optional Command(s) to run when session setup is done.
-    /// Can be a single command (string), or several commands
-    /// (list of strings).
+    /// Optional Command(s) to run before the tmux session is created, only
+    /// when it isn't running already. Can be a single command (string), or
+    /// several commands (list of strings).
+    #[serde(default)]
+    #[serde(deserialize_with = "stringorvec::deserialize_optional_vec_or_string")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_project_first_start: Option<Vec<String>>,
+    /// Optional Command(s) to run when the session is already running, and
+    /// the project is started again. Can be a single command (string), or
+    /// several commands (list of strings).
+    #[serde(default)]
+    #[serde(deserialize_with = "stringorvec::deserialize_optional_vec_or_string")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_project_restart: Option<Vec<String>>,
+    /// Optional Command(s) to run when detaching from the session (after
+    /// attaching returns). Can be a single command (string), or several
+    /// commands (list of strings).
     #[serde(default)]
     #[serde(deserialize_with = "stringorvec::deserialize_optional_vec_or_string")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub on_project_exit: Option<Vec<String>>,
-    /// Optional Command(s) to run when the tmux session is killed, using the
+    /// Optional Command(s) to run before the tmux session is killed, using the
     /// `stop` command. Can be a single command (string), or several commands
     /// (list of strings).
     #[serde(default)]
     #[serde(deserialize_with = "stringorvec::deserialize_optional_vec_or_string")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub on_project_stop: Option<Vec<String>>,
+    /// Deprecated tmuxinator option, runs right before
+    /// `on_project_first_start`, only when the session isn't running already.
+    /// Use `on_project_first_start` instead.
+    #[serde(default)]
+    #[serde(deserialize_with = "stringorvec::deserialize_optional_vec_or_string")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pre: Option<Vec<String>>,
+    /// Deprecated tmuxinator option, runs right before `on_project_exit`.
+    /// Use `on_project_exit` or `on_project_stop` instead.
+    #[serde(default)]
+    #[serde(deserialize_with = "stringorvec::deserialize_optional_vec_or_string")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post: Option<Vec<String>>,
     /// Optional Command(s) to run when in each newly created pane (e.g. activate
     /// a virtualenv). Can be a single command (string), or several commands
     /// (list of strings).
     #[serde(default)]
     #[serde(deserialize_with = "stringorvec::deserialize_optional_vec_or_string")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub pre_window: Option<Vec<String>>,
+    /// Environment variables of the session (optional), set for all of its
+    /// panes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub env: Option<BTreeMap<String, String>>,
+    /// Attach to the session once it's ready (optional, defaults to `true`).
+    /// When `false` the session is created detached.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attach: Option<bool>,
+    /// Pipe the output of the panes to log files (optional), see
+    /// [`LogSetting`]. Can be overridden per window.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log: Option<LogSetting>,
     /// Optional list of [`Window`]s to create.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub windows: Option<Vec<Window>>,
+    /// Optional list of additional [`Session`]s, created after the project's
+    /// session and stopped with it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sessions: Option<Vec<Session>>,
+}
+
+/// Defaults for the projects' settings, the `defaults` section of the global
+/// settings file. For example:
+///
+/// ```yaml
+/// defaults:
+///   layout: main-vertical
+///   pre_window: source .envrc
+///   attach: false
+///   env:
+///     EDITOR: nvim
+/// ```
+///
+/// The project's own settings take precedence.
+#[derive(Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct ProjectDefaults {
+    /// The layout of windows which don't set one, instead of `tiled`.
+    pub layout: Option<String>,
+    /// Command(s) to run in each newly created pane, for projects which don't
+    /// set `pre_window`.
+    #[serde(deserialize_with = "stringorvec::deserialize_optional_vec_or_string")]
+    pub pre_window: Option<Vec<String>>,
+    /// Attach to the session, for projects which don't set `attach`.
+    pub attach: Option<bool>,
+    /// Environment variables of the sessions, merged with the project's `env`.
+    pub env: BTreeMap<String, String>,
+}
+
+impl ProjectConfig {
+    /// Applies the defaults to the settings the project doesn't set, see
+    /// [`ProjectDefaults`].
+    pub fn with_defaults(mut self, defaults: &ProjectDefaults) -> Self {
+        if let Some(layout) = &defaults.layout {
+            let session_windows = self
+                .sessions
+                .iter_mut()
+                .flatten()
+                .flat_map(|session| session.windows.iter_mut());
+            for window in self.windows.iter_mut().flatten().chain(session_windows) {
+                window.layout.get_or_insert_with(|| layout.clone());
+            }
+        }
+        if self.pre_window.is_none() {
+            self.pre_window.clone_from(&defaults.pre_window);
+        }
+        self.attach = self.attach.or(defaults.attach);
+        if !defaults.env.is_empty() {
+            let mut env = defaults.env.clone();
+            env.extend(self.env.take().unwrap_or_default());
+            self.env = Some(env);
+        }
+        self
+    }
+
+    /// Returns warnings for deprecated options used in the project file.
+    pub fn deprecations(&self) -> Vec<&'static str> {
+        let mut warnings = vec![];
+        if self.pre.is_some() {
+            warnings.push(
+                "The pre option is deprecated, use on_project_start or on_project_first_start instead",
+            );
+        }
+        if self.post.is_some() {
+            warnings.push(
+                "The post option is deprecated, use on_project_exit or on_project_stop instead",
+            );
+        }
+        warnings
+    }
+
+    /// Returns the shell commands the project runs, each with the option
+    /// (or window) it's set in, e.g. for reviewing an untrusted project file.
+    pub fn commands(&self) -> Vec<(String, &str)> {
+        let hooks = [
+            ("on_project_start", &self.on_project_start),
+            ("on_project_first_start", &self.on_project_first_start),
+            ("on_project_restart", &self.on_project_restart),
+            ("on_project_exit", &self.on_project_exit),
+            ("on_project_stop", &self.on_project_stop),
+            ("pre", &self.pre),
+            ("post", &self.post),
+            ("pre_window", &self.pre_window),
+        ];
+        let mut commands: Vec<(String, &str)> = hooks
+            .into_iter()
+            .flat_map(|(option, hook)| {
+                hook.iter()
+                    .flatten()
+                    .map(move |command| (option.to_string(), command.as_str()))
+            })
+            .collect();
+        for window in self.windows.iter().flatten() {
+            for command in window.panes.iter().flatten().flatten() {
+                commands.push((format!("window {:?}", window.name), command));
+            }
+        }
+        for session in self.sessions.iter().flatten() {
+            for window in &session.windows {
+                for command in window.panes.iter().flatten().flatten() {
+                    commands.push((
+                        format!("session {:?} window {:?}", session.name, window.name),
+                        command,
+                    ));
+                }
+            }
+        }
+        commands
+    }
+
+    /// Serializes the project to the `.yml` file format.
+    pub fn to_yaml(&self) -> Result<String, serde_yaml::Error> {
+        serde_yaml::to_string(self)
+    }
 }
 
 impl TryFrom<Value> for ProjectConfig {
@@ -86,7 +264,8 @@ impl TryFrom<String> for ProjectConfig {
 
 #[cfg(test)]
 mod tests {
-    use super::ProjectConfig;
+    use super::{ProjectConfig, ProjectDefaults};
+    use std::collections::BTreeMap;
     use std::convert::TryFrom;
 
     #[test]
@@ -139,4 +318,123 @@ windows:
         assert_eq!(first.name, window_name);
         assert_eq!(first.panes, vec![Some(vec![window_command])]);
     }
+
+    #[test]
+    fn deprecated_options_test() {
+        let yaml = "project_name: deprecated\npre: echo pre\npost: echo post".to_string();
+        let project = ProjectConfig::try_from(yaml).unwrap();
+        assert_eq!(project.pre, Some(vec!["echo pre".into()]));
+        assert_eq!(project.post, Some(vec!["echo post".into()]));
+        assert_eq!(project.deprecations().len(), 2);
+
+        let project = ProjectConfig::try_from("project_name: current".to_string()).unwrap();
+        assert!(project.deprecations().is_empty());
+    }
+
+    #[test]
+    fn commands_test() {
+        let yaml = "\
+project_name: demo
+on_project_start: make db
+pre_window: workon demo
+windows:
+  - editor: vim
+  - empty:
+  - shells:
+      panes:
+        - null
+        - [cd web, npm start]";
+        let project = ProjectConfig::try_from(yaml.to_string()).unwrap();
+        assert_eq!(
+            project.commands(),
+            vec![
+                ("on_project_start".to_string(), "make db"),
+                ("pre_window".to_string(), "workon demo"),
+                ("window \"editor\"".to_string(), "vim"),
+                ("window \"shells\"".to_string(), "cd web"),
+                ("window \"shells\"".to_string(), "npm start"),
+            ]
+        );
+    }
+
+    #[test]
+    fn metadata_test() {
+        let yaml = "project_name: demo\ndescription: The demo\ntags: work".to_string();
+        let project = ProjectConfig::try_from(yaml).unwrap();
+        assert_eq!(project.description.as_deref(), Some("The demo"));
+        assert_eq!(project.tags, Some(vec!["work".to_string()]));
+
+        let yaml = "project_name: demo\ntags: [work, web]".to_string();
+        let project = ProjectConfig::try_from(yaml).unwrap();
+        assert_eq!(
+            project.tags,
+            Some(vec!["work".to_string(), "web".to_string()])
+        );
+    }
+
+    #[test]
+    fn with_defaults_test() {
+        let defaults: ProjectDefaults = serde_yaml::from_str(
+            "layout: main-vertical\npre_window: workon default\nattach: false\nenv:\n  A: default\n  B: default",
+        )
+        .unwrap();
+        let yaml = "\
+project_name: demo
+env:
+  B: project
+windows:
+  - editor: vim
+  - shells:
+      layout: even-horizontal
+      panes: [null, null]";
+        let project = ProjectConfig::try_from(yaml.to_string())
+            .unwrap()
+            .with_defaults(&defaults);
+        let windows = project.windows.as_ref().unwrap();
+        assert_eq!(windows[0].layout(), "main-vertical");
+        assert_eq!(windows[1].layout(), "even-horizontal");
+        assert_eq!(project.pre_window, Some(vec!["workon default".into()]));
+        assert_eq!(project.attach, Some(false));
+        assert_eq!(
+            project.env,
+            Some(BTreeMap::from([
+                ("A".into(), "default".into()),
+                ("B".into(), "project".into())
+            ]))
+        );
+
+        let yaml = "project_name: own\npre_window: workon own\nattach: true";
+        let project = ProjectConfig::try_from(yaml.to_string())
+            .unwrap()
+            .with_defaults(&defaults);
+        assert_eq!(project.pre_window, Some(vec!["workon own".into()]));
+        assert_eq!(project.attach, Some(true));
+
+        let project = ProjectConfig::try_from("project_name: plain".to_string()).unwrap();
+        assert_eq!(
+            project.with_defaults(&ProjectDefaults::default()),
+            ProjectConfig::try_from("project_name: plain".to_string()).unwrap()
+        );
+    }
+
+    #[test]
+    fn yaml_roundtrip_test() {
+        let yaml = "\
+project_name: demo
+project_root: ~/src/demo
+on_project_start:
+  - sudo systemctl start postgresql
+pre_window: workon demo
+windows:
+  - editor: vim
+  - shells:
+      layout: main-vertical
+      panes:
+        - null
+        - grunt serve";
+        let project = ProjectConfig::try_from(yaml.to_string()).unwrap();
+        let serialized = project.to_yaml().unwrap();
+        assert!(!serialized.contains("on_project_stop"));
+        assert_eq!(ProjectConfig::try_from(serialized).unwrap(), project);
+    }
 }
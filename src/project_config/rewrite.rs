@@ -0,0 +1,211 @@
+//! Rewrites the name and roots of a project file, used by `copy` and
+//! `rename`.
+//!
+//! The lines of the changed keys are edited in place, keeping the file's
+//! comments and formatting. If the result doesn't parse to the expected
+//! project (e.g. a file in flow style), the project is serialized instead,
+//! losing the comments.
+use super::error::ProjectParseError;
+use regex::Regex;
+use serde_yaml::{Mapping, Value};
+use std::path::Path;
+
+const PROJECT_NAME: &str = "project_name";
+const NAME: &str = "name";
+const PROJECT_ROOT: &str = "project_root";
+const WINDOWS: &str = "windows";
+const ROOT: &str = "root";
+
+/// The changes to a project file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProjectRewrite<'a> {
+    /// The new project name.
+    pub name: &'a str,
+    /// The new project root (optional). Window roots inside the old project
+    /// root are moved to the new one.
+    pub root: Option<&'a str>,
+}
+
+impl ProjectRewrite<'_> {
+    /// Applies the changes to the content of a project file.
+    pub fn apply(&self, contents: &str) -> Result<String, ProjectParseError> {
+        let mut project: Value = parse(contents)?;
+        let Some(mapping) = project.as_mapping_mut() else {
+            return Err(ProjectParseError("expected a mapping".into()));
+        };
+        let old_root = string_value(mapping, PROJECT_ROOT);
+        let name_key = match mapping.contains_key(PROJECT_NAME) || !mapping.contains_key(NAME) {
+            true => PROJECT_NAME,
+            false => NAME,
+        };
+        mapping.insert(name_key.into(), self.name.into());
+        if let Some(root) = self.root {
+            mapping.insert(PROJECT_ROOT.into(), root.into());
+            if let Some(Value::Sequence(windows)) = mapping.get_mut(WINDOWS) {
+                for window in windows.iter_mut().filter_map(Value::as_mapping_mut) {
+                    for definition in window.values_mut().filter_map(Value::as_mapping_mut) {
+                        let moved = string_value(definition, ROOT)
+                            .and_then(|window_root| self.move_root(&window_root, &old_root));
+                        if let Some(moved) = moved {
+                            definition.insert(ROOT.into(), moved.into());
+                        }
+                    }
+                }
+            }
+        }
+
+        let rewritten = self.rewrite_lines(contents, name_key, &old_root);
+        if parse(&rewritten).is_ok_and(|value| value == project) {
+            return Ok(rewritten);
+        }
+        serde_yaml::to_string(&project).map_err(|e| ProjectParseError(format!("{e}")))
+    }
+
+    /// Returns the window root moved from the old project root to the new
+    /// one, if it's inside the old project root.
+    fn move_root(&self, window_root: &str, old_root: &Option<String>) -> Option<String> {
+        let (old_root, root) = (old_root.as_deref()?, self.root?);
+        let relative = Path::new(window_root).strip_prefix(old_root).ok()?;
+        match relative.as_os_str().is_empty() {
+            true => Some(root.to_string()),
+            false => Some(
+                Path::new(root)
+                    .join(relative)
+                    .to_string_lossy()
+                    .into_owned(),
+            ),
+        }
+    }
+
+    /// Edits the lines of the changed keys, inserting `project_root` after
+    /// the name if missing.
+    fn rewrite_lines(&self, contents: &str, name_key: &str, old_root: &Option<String>) -> String {
+        let line_re = Regex::new(r"^(\s*)([A-Za-z_]+)(\s*:\s*)(.*?)(\s+#.*)?$").unwrap();
+        let mut lines = vec![];
+        let mut section = String::new();
+        for line in contents.lines() {
+            let Some(caps) = line_re.captures(line) else {
+                lines.push(line.to_string());
+                continue;
+            };
+            let (indent, key, value) = (&caps[1], &caps[2], &caps[4]);
+            if indent.is_empty() {
+                section = key.to_string();
+            }
+            let new_value = match (indent.is_empty(), key) {
+                (true, key) if key == name_key => Some(self.name.to_string()),
+                (true, PROJECT_ROOT) => self.root.map(String::from),
+                (false, ROOT) if section == WINDOWS => serde_yaml::from_str::<String>(value)
+                    .ok()
+                    .and_then(|window_root| self.move_root(&window_root, old_root)),
+                _ => None,
+            };
+            let Some(new_value) = new_value else {
+                lines.push(line.to_string());
+                continue;
+            };
+            lines.push(format!(
+                "{indent}{key}{}{}{}",
+                &caps[3],
+                scalar(&new_value),
+                caps.get(5).map_or("", |comment| comment.as_str())
+            ));
+            if key == name_key && old_root.is_none() {
+                if let Some(root) = self.root {
+                    lines.push(format!("{PROJECT_ROOT}: {}", scalar(root)));
+                }
+            }
+        }
+        let mut rewritten = lines.join("\n");
+        if contents.ends_with('\n') {
+            rewritten.push('\n');
+        }
+        rewritten
+    }
+}
+
+/// Parses the content to [`Value`].
+fn parse(contents: &str) -> Result<Value, ProjectParseError> {
+    serde_yaml::from_str(contents).map_err(|e| ProjectParseError(format!("{e}")))
+}
+
+/// Returns the string value of the key, if set.
+fn string_value(mapping: &Mapping, key: &str) -> Option<String> {
+    mapping.get(key).and_then(Value::as_str).map(String::from)
+}
+
+/// Formats the string as a yaml scalar, quoted if needed.
+fn scalar(value: &str) -> String {
+    serde_yaml::to_string(value)
+        .map(|yaml| yaml.trim_end().to_string())
+        .unwrap_or_else(|_| value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ProjectRewrite;
+
+    const PROJECT: &str = "\
+# The demo project
+project_name: demo # the session's name
+project_root: ~/src/demo
+windows:
+  - editor: vim
+  - server:
+      root: ~/src/demo/server # the server's code
+      panes:
+        - npm start
+  - logs:
+      root: /var/log
+";
+
+    #[test]
+    fn rewrite_name() {
+        let rewrite = ProjectRewrite {
+            name: "blog",
+            root: None,
+        };
+        assert_eq!(
+            rewrite.apply(PROJECT).unwrap(),
+            PROJECT.replace("project_name: demo", "project_name: blog")
+        );
+        assert_eq!(
+            rewrite.apply("name: demo\n").unwrap(),
+            "name: blog\n".to_string()
+        );
+    }
+
+    #[test]
+    fn rewrite_root() {
+        let rewrite = ProjectRewrite {
+            name: "blog",
+            root: Some("~/src/blog"),
+        };
+        assert_eq!(
+            rewrite.apply(PROJECT).unwrap(),
+            PROJECT
+                .replace("project_name: demo", "project_name: blog")
+                .replace("~/src/demo", "~/src/blog")
+        );
+        assert_eq!(
+            rewrite
+                .apply("project_name: demo\nwindows:\n  - editor: vim\n")
+                .unwrap(),
+            "project_name: blog\nproject_root: ~/src/blog\nwindows:\n  - editor: vim\n"
+        );
+    }
+
+    #[test]
+    fn rewrite_flow_style() {
+        let rewrite = ProjectRewrite {
+            name: "blog",
+            root: Some("/srv/blog"),
+        };
+        assert_eq!(
+            rewrite
+                .apply("{project_name: demo, project_root: /srv/demo}")
+                .unwrap(),
+            "project_name: blog\nproject_root: /srv/blog\n"
+        );
+    }
+}
@@ -0,0 +1,36 @@
+//! Handles the optional `sessions` section of a project, additional tmux
+//! sessions started and stopped with the project's own session.
+use super::window::Window;
+use serde::{Deserialize, Serialize};
+
+/// An additional session of the project. For example:
+///
+/// ```yaml
+/// project_name: app
+/// windows:
+///   - server: npm start
+///   - logs: tail -f log/development.log
+/// sessions:
+///   - name: app-ops
+///     root: ~/src/app/ops
+///     windows:
+///       - deploy:
+///       - logs:
+///           link: app:logs
+/// ```
+///
+/// The project's hooks, `pre_window`, `env` and `log` settings apply to the
+/// additional sessions as well. They're created after the project's own
+/// session, which is the one attached to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Session {
+    /// The session's name.
+    pub name: String,
+    /// The root directory of the session's windows (optional), defaults to
+    /// the project root.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub root: Option<String>,
+    /// The session's [`Window`]s.
+    #[serde(default)]
+    pub windows: Vec<Window>,
+}
@@ -1,10 +1,12 @@
 //! Handles a [Window] de-serialization, an optional section
 //! of project's configuration.
 use super::error::ProjectParseError;
+use super::log::LogSetting;
 use serde::de::{self, MapAccess, Visitor};
-use serde::{Deserialize, Deserializer, Serialize};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_yaml::{self, Value};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::convert::TryFrom;
 use std::fmt;
 
@@ -49,13 +51,22 @@ use std::fmt;
 /// ```
 /// Will be a window named `window4` with 3 panes running `vim`, nothing,
 /// & `npm run serve` and `main-vertical` layout.
-#[derive(Debug, PartialEq, Eq, Serialize)]
+///
+/// A pane running several commands can be a list of the commands, or a
+/// mapping of the pane's name to the list.
+///
+/// ```yml
+/// logs:
+///   link: app:logs
+/// ```
+/// Will be the window `logs` of the running session `app`, shared with
+/// `tmux link-window` instead of creating a window.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Window {
     /// The name of the window
     pub name: String,
-    /// The tmux layout of the window. Defaults to `tiled`.
-    #[serde(default = "tiled")]
-    pub layout: String,
+    /// The tmux layout of the window (optional), see [`Window::layout`].
+    pub layout: Option<String>,
     /// The window's panes, each with an optional command(s) to run.
     pub panes: Vec<Option<Vec<String>>>,
     /// Per window options
@@ -65,6 +76,25 @@ pub struct Window {
     ///
     /// Takes precedence over the project_root.
     pub root: Option<String>,
+    /// Pipe the output of the window's panes to log files (optional), see
+    /// [`LogSetting`].
+    ///
+    /// Takes precedence over the project's log setting.
+    pub log: Option<LogSetting>,
+    /// The `session:window` target of a window in another session to link,
+    /// instead of creating the window (optional). A linked window has no
+    /// panes of its own.
+    pub link: Option<String>,
+}
+
+/// The layout of windows which don't set one.
+pub const DEFAULT_LAYOUT: &str = "tiled";
+
+impl Window {
+    /// Returns the window's layout, defaults to [`DEFAULT_LAYOUT`].
+    pub fn layout(&self) -> &str {
+        self.layout.as_deref().unwrap_or(DEFAULT_LAYOUT)
+    }
 }
 
 impl TryFrom<String> for Window {
@@ -75,6 +105,79 @@ impl TryFrom<String> for Window {
     }
 }
 
+impl Serialize for Window {
+    /// Serializes the window in the same format it's de-serialized from, a
+    /// mapping of the name to the pane's command (for a single pane window
+    /// with the default layout), or to the window's settings.
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let panes: Vec<_> = self.panes.iter().map(PaneBody::from).collect();
+        let mut map = serializer.serialize_map(Some(1))?;
+        let is_simple = self.layout.is_none()
+            && self.options.is_none()
+            && self.root.is_none()
+            && self.log.is_none()
+            && self.link.is_none()
+            && matches!(panes.as_slice(), [PaneBody::Empty | PaneBody::Single(_)]);
+        if is_simple {
+            map.serialize_entry(&self.name, &panes[0])?;
+        } else {
+            let body = WindowBody {
+                layout: self.layout.as_ref(),
+                root: self.root.as_ref(),
+                options: self
+                    .options
+                    .as_ref()
+                    .map(|options| options.iter().collect()),
+                log: self.log.as_ref(),
+                link: self.link.as_ref(),
+                panes,
+            };
+            map.serialize_entry(&self.name, &body)?;
+        }
+        map.end()
+    }
+}
+
+/// The settings of a [Window], used for serializing it.
+#[derive(Serialize)]
+struct WindowBody<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    layout: Option<&'a String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    root: Option<&'a String>,
+    /// Sorted, for a stable output.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    options: Option<BTreeMap<&'a String, &'a String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    log: Option<&'a LogSetting>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    link: Option<&'a String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    panes: Vec<PaneBody<'a>>,
+}
+
+/// The command(s) of a pane, used for serializing a [Window].
+#[derive(Serialize)]
+#[serde(untagged)]
+enum PaneBody<'a> {
+    Empty,
+    Single(&'a String),
+    Multiple(&'a Vec<String>),
+}
+
+impl<'a> From<&'a Option<Vec<String>>> for PaneBody<'a> {
+    fn from(pane: &'a Option<Vec<String>>) -> Self {
+        match pane.as_deref() {
+            None | Some([]) => PaneBody::Empty,
+            Some([command]) => PaneBody::Single(command),
+            Some(_) => PaneBody::Multiple(pane.as_ref().unwrap()),
+        }
+    }
+}
+
 impl<'de> Deserialize<'de> for Window {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -102,10 +205,12 @@ impl<'de> Visitor<'de> for WindowVisitor {
         let (key, val) = map.next_entry::<String, Value>().unwrap().unwrap();
         let mut w = Window {
             name: key,
-            layout: "tiled".into(),
+            layout: None,
             panes: vec![],
             options: None,
             root: None,
+            log: None,
+            link: None,
         };
 
         match val {
@@ -114,13 +219,36 @@ impl<'de> Visitor<'de> for WindowVisitor {
             Value::Mapping(map) => {
                 w.layout = map
                     .get(Value::String("layout".into()))
-                    .map(|v| v.as_str().unwrap().into())
-                    .unwrap_or_else(|| "tiled".into());
+                    .map(|v| v.as_str().unwrap().into());
 
                 w.root = map
                     .get(Value::String("root".into()))
                     .map(|v| v.as_str().unwrap().into());
 
+                w.link = map
+                    .get(Value::String("link".into()))
+                    .map(|v| {
+                        v.as_str().map(String::from).ok_or_else(|| {
+                            de::Error::custom(format!(
+                                "link of window {:?} must be a string",
+                                w.name
+                            ))
+                        })
+                    })
+                    .transpose()?;
+                if w.link.is_some() && map.contains_key(Value::String("panes".into())) {
+                    return Err(de::Error::custom(format!(
+                        "linked window {:?} can not have panes",
+                        w.name
+                    )));
+                }
+
+                w.log = map
+                    .get(Value::String("log".into()))
+                    .map(|v| serde_yaml::from_value(v.clone()))
+                    .transpose()
+                    .map_err(de::Error::custom)?;
+
                 w.options = map.get(Value::String("options".into())).map(|v| {
                     v.as_mapping()
                         .unwrap()
@@ -138,6 +266,13 @@ impl<'de> Visitor<'de> for WindowVisitor {
                     for pane in panes {
                         match pane {
                             Value::String(pane_cmd) => w.panes.push(Some(vec![pane_cmd.into()])),
+                            Value::Sequence(pane_cmds) => w.panes.push(Some(
+                                pane_cmds
+                                    .iter()
+                                    .filter_map(|cmd| cmd.as_str())
+                                    .map(|cmd| cmd.into())
+                                    .collect(),
+                            )),
                             Value::Mapping(multicommand_pane) => {
                                 for pane_commands in multicommand_pane.values() {
                                     match pane_commands {
@@ -185,15 +320,15 @@ mod tests {
             .collect();
 
         assert_eq!(windows[0].name, "test");
-        assert_eq!(windows[0].layout, "tiled".to_string());
+        assert_eq!(windows[0].layout(), "tiled");
         assert_eq!(windows[0].panes, vec![None]);
 
         assert_eq!(windows[1].name, "test2 window");
-        assert_eq!(windows[1].layout, "tiled".to_string());
+        assert_eq!(windows[1].layout(), "tiled");
         assert_eq!(windows[1].panes, vec![Some(vec!["vim".into()])]);
 
         assert_eq!(windows[2].name, "window3");
-        assert_eq!(windows[2].layout, "tiled".to_string());
+        assert_eq!(windows[2].layout(), "tiled");
         assert_eq!(
             windows[2].panes,
             vec![
@@ -204,7 +339,7 @@ mod tests {
         );
 
         assert_eq!(windows[3].name, "window4");
-        assert_eq!(windows[3].layout, "main-vertical".to_string());
+        assert_eq!(windows[3].layout(), "main-vertical");
         assert_eq!(
             windows[3].panes,
             vec![
@@ -215,6 +350,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn linked_window() {
+        let window = Window::try_from("logs:\n  link: app:logs".to_string()).unwrap();
+        assert_eq!(window.link.as_deref(), Some("app:logs"));
+        assert!(window.panes.is_empty());
+        assert_eq!(
+            serde_yaml::to_string(&window).unwrap(),
+            "logs:\n  link: app:logs\n"
+        );
+
+        let invalid = "logs:\n  link: app:logs\n  panes:\n    - tail -f log";
+        assert!(Window::try_from(invalid.to_string()).is_err());
+    }
+
+    #[test]
+    fn non_string_link() {
+        for link in ["2", "[a]", "{a: b}"] {
+            let err = Window::try_from(format!("logs:\n  link: {link}")).unwrap_err();
+            assert!(err.0.contains("link of window \"logs\" must be a string"));
+        }
+    }
+
     #[test]
     fn multicommand_panes() {
         let yaml = "\
@@ -307,4 +464,59 @@ window-wiout-root:";
 
         assert_eq!(window.root, None);
     }
+
+    #[test]
+    fn window_log() {
+        let yaml = "\
+window-with-log:
+    log: ~/logs";
+        let window = Window::try_from(yaml.to_string()).unwrap();
+        assert_eq!(window.log, Some(LogSetting::Dir("~/logs".into())));
+
+        let yaml = "\
+window-without-log:
+    log: false";
+        let window = Window::try_from(yaml.to_string()).unwrap();
+        assert_eq!(window.log, Some(LogSetting::Enabled(false)));
+    }
+
+    #[test]
+    fn list_pane() {
+        let yaml = "\
+list-window:
+  panes:
+    - - echo one
+      - echo two";
+        let window = Window::try_from(yaml.to_string()).unwrap();
+        assert_eq!(
+            window.panes,
+            vec![Some(vec!["echo one".into(), "echo two".into()])]
+        );
+    }
+
+    #[test]
+    fn serialize_roundtrip() {
+        let windows = [
+            "editor: vim\n",
+            "shell: null\n",
+            "\
+logs:
+  layout: main-vertical
+  root: ~/logs
+  options:
+    main-pane-height: 70%
+    main-pane-width: 75%
+  log: true
+  panes:
+  - tail -f app.log
+  - null
+  - - cd archive
+    - ls
+",
+        ];
+        for yaml in windows {
+            let window = Window::try_from(yaml.to_string()).unwrap();
+            assert_eq!(serde_yaml::to_string(&window).unwrap(), yaml);
+        }
+    }
 }
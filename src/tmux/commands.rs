@@ -1,10 +1,14 @@
 //! The various tmux operations commands.
-use super::project::TMUX_BIN;
+use super::server::{tmux_command, tmux_prefix, TmuxServer};
 use super::TmuxError;
 
 use clap::crate_name;
+use serde::Serialize;
 use shlex::Shlex;
+use std::collections::BTreeMap;
 use std::env::set_current_dir;
+use std::fs::create_dir_all;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::{env, fmt};
 
@@ -21,15 +25,24 @@ pub(crate) enum Commands<'a> {
         project_name: &'a str,
         project_root: &'a Option<String>,
     },
-    /// Runs the various `on_project_<event>` commands.
+    /// Runs the commands of a project hook, e.g. `on_project_start`.
     ProjectEvent {
-        event_name: &'a str,
+        hook_name: &'a str,
         on_event: &'a Option<Vec<String>>,
     },
     /// Start the new tmux session, and cd again (for tmux < 1.9 compat).
     Session {
         project_name: &'a str,
         first_window_name: Option<&'a str>,
+        /// Environment variables of the session, `new-session -e` (tmux >= 3.2).
+        env: Option<&'a BTreeMap<String, String>>,
+    },
+    /// `set-environment` command, for the session's environment variables
+    /// with tmux < 3.2.
+    SetEnvironment {
+        session_name: &'a str,
+        name: &'a str,
+        value: &'a str,
     },
     /// `send-keys` command.
     SendKeys {
@@ -46,6 +59,13 @@ pub(crate) enum Commands<'a> {
         window_index: usize,
         window_root: Option<String>,
     },
+    /// `link-window` command, linking a window of another session in place
+    /// of the window at the index.
+    LinkWindow {
+        session_name: &'a str,
+        window_index: usize,
+        source: &'a str,
+    },
     /// `split-window` command.
     SplitWindow {
         session_name: &'a str,
@@ -76,11 +96,47 @@ pub(crate) enum Commands<'a> {
         window_index: usize,
         pane_index: usize,
     },
+    /// `pipe-pane` command, appending the pane's output to a log file.
+    PipePane {
+        session_name: &'a str,
+        window_index: usize,
+        pane_index: usize,
+        log_file: PathBuf,
+    },
     /// Attaches to a session using `attach-sesssion` or `switch-client`,
     /// depends upon already running inside a tmux session or out of it.
-    AttachSession { session_name: &'a str },
+    ///
+    /// With a `client` (e.g. `/dev/pts/3`) switches that client to the
+    /// session instead, inside tmux or out of it. A switched client (the
+    /// current one inside tmux) is set read-only with `read_only`, and the
+    /// other clients of the session are detached with `detach_others`, like
+    /// `attach-session -r -d` does.
+    AttachSession {
+        session_name: &'a str,
+        read_only: bool,
+        detach_others: bool,
+        client: Option<&'a str>,
+    },
+    /// Creates a view of a session, a new session grouped with it (sharing
+    /// its windows) with its own current window, and attaches (or switches)
+    /// to it like [`Commands::AttachSession`].
+    ///
+    /// The view is destroyed once detached (`destroy-unattached`), which is
+    /// set once it's attached since tmux destroys an unattached session with
+    /// the option right away.
+    AttachView {
+        session_name: &'a str,
+        view_name: &'a str,
+        window_index: Option<usize>,
+        client: Option<&'a str>,
+    },
     /// `kill-session` command
     StopSession { session_name: &'a str },
+    /// `rename-session` command
+    RenameSession {
+        session_name: &'a str,
+        new_name: &'a str,
+    },
     /// Set a hook for tmux events
     SetHook {
         session_name: &'a str,
@@ -89,7 +145,481 @@ pub(crate) enum Commands<'a> {
     },
 }
 
+/// A structured representation of a [`Commands`] step, used for the `json`
+/// format of the `debug` cli command.
+#[derive(Debug, Serialize)]
+pub(crate) struct PlanStep<'a> {
+    /// The type of the step, the tmux command (e.g. `new-window`), or `hook`.
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    /// The name of the hook, e.g. `on_project_start`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<&'a str>,
+    /// The session, window and pane the step applies to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<PlanTarget<'a>>,
+    /// The working directory, if set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub root: Option<&'a str>,
+    /// The tmux arguments, or the shell commands of a hook.
+    pub args: Vec<String>,
+}
+
+/// The target of a [`PlanStep`].
+#[derive(Debug, Serialize)]
+pub(crate) struct PlanTarget<'a> {
+    pub session: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pane: Option<usize>,
+}
+
+/// Quotes an argument for the shell (or tmux's configuration parser),
+/// leaving it as is when it contains no special characters (e.g. targets like
+/// `session:1.0`).
+pub(crate) fn quote(arg: &str) -> String {
+    let is_safe = |c: char| c.is_ascii_alphanumeric() || "-_./:,+=@%".contains(c);
+    if !arg.is_empty() && arg.chars().all(is_safe) {
+        arg.into()
+    } else {
+        shell_escape::escape(arg.into()).into_owned()
+    }
+}
+
+/// Quotes a path for the shell (or tmux's configuration parser), leaving a
+/// leading `~/` unquoted so it's still expanded, like when running the
+/// commands.
+pub(crate) fn quote_path(path: &str) -> String {
+    match path.strip_prefix("~/") {
+        Some(rest) if !rest.is_empty() => format!("~/{}", quote(rest)),
+        _ => quote(path),
+    }
+}
+
+/// Runs hook commands outside of a project, e.g. the ones of a group, like
+/// the project's hooks.
+pub(crate) fn run_hook(hook_name: &str, commands: &Option<Vec<String>>) -> Result<(), TmuxError> {
+    Commands::ProjectEvent {
+        hook_name,
+        on_event: commands,
+    }
+    .run()
+}
+
+/// Quotes the arguments and joins them to a single command line. The value
+/// of `-c` (the start directory) is quoted as a path.
+pub(crate) fn join_args(args: &[String]) -> String {
+    let mut quoted = Vec::with_capacity(args.len());
+    for (idx, arg) in args.iter().enumerate() {
+        if idx > 0 && args[idx - 1] == "-c" {
+            quoted.push(quote_path(arg));
+        } else {
+            quoted.push(quote(arg));
+        }
+    }
+    quoted.join(" ")
+}
+
+/// Splits a hook's command to the arguments it's executed with (hooks are
+/// executed directly, not with a shell).
+fn hook_args(command: &str) -> Vec<String> {
+    Shlex::new(command).collect()
+}
+
 impl<'a> Commands<'a> {
+    /// Returns `true` for commands which do nothing, e.g. hooks which are not
+    /// set. Those are skipped when displaying the commands.
+    pub fn is_noop(&self) -> bool {
+        matches!(self, Commands::ProjectEvent { on_event: None, .. })
+    }
+
+    /// Returns the tmux arguments of the command (without the tmux binary),
+    /// `None` for commands which aren't tmux commands (e.g. hooks).
+    ///
+    /// Paths are returned as specified in the project, before expansion.
+    pub fn args(&self) -> Option<Vec<String>> {
+        self.build_args(|root| Ok(root.to_string())).ok().flatten()
+    }
+
+    /// Returns the tmux arguments used when running the command, same as
+    /// [`args`](Self::args) with the paths expanded.
+    fn run_args(&self) -> Result<Option<Vec<String>>, TmuxError> {
+        self.build_args(|root| Ok(shellexpand::full(root)?.to_string()))
+    }
+
+    /// Helper building the tmux arguments, mapping the paths with `path`.
+    fn build_args(
+        &self,
+        path: impl Fn(&str) -> Result<String, TmuxError>,
+    ) -> Result<Option<Vec<String>>, TmuxError> {
+        let target =
+            |session_name: &str, window_index: usize, pane_index: Option<usize>| match pane_index {
+                Some(pane_index) => format!("{session_name}:{window_index}.{pane_index}"),
+                None => format!("{session_name}:{window_index}"),
+            };
+        let with_root = |mut args: Vec<String>, root: &Option<String>| {
+            if let Some(root) = root {
+                args.extend(["-c".into(), path(root)?]);
+            }
+            Ok::<_, TmuxError>(args)
+        };
+
+        let args: Vec<String> = match self {
+            Commands::Server { .. } => vec!["start-server".into()],
+            Commands::ProjectEvent { .. } => return Ok(None),
+            Commands::Session {
+                project_name,
+                first_window_name,
+                env,
+            } => {
+                let mut args = vec![
+                    "new-session".into(),
+                    "-d".into(),
+                    "-s".into(),
+                    project_name.to_string(),
+                ];
+                if let Some(name) = first_window_name {
+                    args.extend(["-n".into(), name.to_string()]);
+                }
+                for (name, value) in env.iter().flat_map(|env| env.iter()) {
+                    args.extend(["-e".into(), format!("{name}={value}")]);
+                }
+                args
+            }
+            Commands::SendKeys {
+                command,
+                session_name,
+                window_index,
+                pane_index,
+                comment: _,
+            } => vec![
+                "send-keys".into(),
+                "-t".into(),
+                target(session_name, *window_index, *pane_index),
+                command.clone(),
+                "C-m".into(),
+            ],
+            Commands::NewWindow {
+                session_name,
+                window_name,
+                window_index,
+                window_root,
+            } => with_root(
+                vec![
+                    "new-window".into(),
+                    "-k".into(),
+                    "-t".into(),
+                    target(session_name, *window_index, None),
+                    "-n".into(),
+                    window_name.to_string(),
+                ],
+                window_root,
+            )?,
+            Commands::LinkWindow {
+                session_name,
+                window_index,
+                source,
+            } => vec![
+                "link-window".into(),
+                "-k".into(),
+                "-s".into(),
+                source.to_string(),
+                "-t".into(),
+                target(session_name, *window_index, None),
+            ],
+            Commands::SplitWindow {
+                session_name,
+                window_index,
+                window_root,
+            } => with_root(
+                vec![
+                    "splitw".into(),
+                    "-t".into(),
+                    target(session_name, *window_index, None),
+                ],
+                window_root,
+            )?,
+            Commands::SelectLayout {
+                session_name,
+                window_index,
+                layout,
+            } => vec![
+                "select-layout".into(),
+                "-t".into(),
+                target(session_name, *window_index, None),
+                layout.to_string(),
+            ],
+            Commands::SelectWindow {
+                session_name,
+                window_index,
+            } => vec![
+                "select-window".into(),
+                "-t".into(),
+                target(session_name, *window_index, None),
+            ],
+            Commands::SetWindowOption {
+                session_name,
+                window_index,
+                option_name,
+                value,
+            } => vec![
+                "set-option".into(),
+                "-w".into(),
+                "-t".into(),
+                target(session_name, *window_index, None),
+                option_name.to_string(),
+                value.to_string(),
+            ],
+            Commands::SelectPane {
+                session_name,
+                window_index,
+                pane_index,
+            } => vec![
+                "select-pane".into(),
+                "-t".into(),
+                target(session_name, *window_index, Some(*pane_index)),
+            ],
+            Commands::PipePane {
+                session_name,
+                window_index,
+                pane_index,
+                log_file,
+            } => vec![
+                "pipe-pane".into(),
+                "-o".into(),
+                "-t".into(),
+                target(session_name, *window_index, Some(*pane_index)),
+                Commands::get_pipe_command(log_file),
+            ],
+            Commands::AttachSession {
+                session_name,
+                read_only,
+                client: Some(client),
+                ..
+            } => Commands::client_args(session_name, client, *read_only),
+            Commands::AttachSession {
+                session_name,
+                read_only,
+                detach_others,
+                client: None,
+            } => {
+                let mut args = vec!["-u".into(), "attach-session".into()];
+                if *read_only {
+                    args.push("-r".into());
+                }
+                if *detach_others {
+                    args.push("-d".into());
+                }
+                args.extend(["-t".into(), session_name.to_string()]);
+                args
+            }
+            Commands::AttachView {
+                session_name,
+                view_name,
+                window_index,
+                client,
+            } => Commands::view_args(session_name, view_name, *window_index, false, *client),
+            Commands::StopSession { session_name } => {
+                vec!["kill-session".into(), "-t".into(), session_name.to_string()]
+            }
+            Commands::RenameSession {
+                session_name,
+                new_name,
+            } => vec![
+                "rename-session".into(),
+                "-t".into(),
+                session_name.to_string(),
+                new_name.to_string(),
+            ],
+            Commands::SetEnvironment {
+                session_name,
+                name,
+                value,
+            } => vec![
+                "set-environment".into(),
+                "-t".into(),
+                session_name.to_string(),
+                name.to_string(),
+                value.to_string(),
+            ],
+            Commands::SetHook {
+                session_name,
+                hook_name,
+                hook_command,
+            } => vec![
+                "set-hook".into(),
+                "-t".into(),
+                session_name.to_string(),
+                hook_name.to_string(),
+                hook_command.clone(),
+            ],
+        };
+        Ok(Some(args))
+    }
+
+    /// Returns the structured [`PlanStep`] of the command.
+    pub fn plan_step(&self) -> PlanStep<'_> {
+        let target = |session: &'a str, window: Option<usize>, pane: Option<usize>| {
+            Some(PlanTarget {
+                session,
+                window,
+                pane,
+            })
+        };
+
+        let (target, root) = match self {
+            Commands::Server {
+                project_name,
+                project_root,
+            } => (target(project_name, None, None), project_root.as_deref()),
+            Commands::ProjectEvent {
+                hook_name,
+                on_event,
+            } => {
+                return PlanStep {
+                    kind: self.kind(),
+                    name: Some(hook_name),
+                    target: None,
+                    root: None,
+                    args: on_event.as_ref().cloned().unwrap_or_default(),
+                }
+            }
+            Commands::Session { project_name, .. } => (target(project_name, None, None), None),
+            Commands::SendKeys {
+                session_name,
+                window_index,
+                pane_index,
+                ..
+            } => (target(session_name, Some(*window_index), *pane_index), None),
+            Commands::NewWindow {
+                session_name,
+                window_index,
+                window_root,
+                ..
+            }
+            | Commands::SplitWindow {
+                session_name,
+                window_index,
+                window_root,
+            } => (
+                target(session_name, Some(*window_index), None),
+                window_root.as_deref(),
+            ),
+            Commands::SelectLayout {
+                session_name,
+                window_index,
+                ..
+            }
+            | Commands::SelectWindow {
+                session_name,
+                window_index,
+            }
+            | Commands::LinkWindow {
+                session_name,
+                window_index,
+                ..
+            }
+            | Commands::SetWindowOption {
+                session_name,
+                window_index,
+                ..
+            } => (target(session_name, Some(*window_index), None), None),
+            Commands::SelectPane {
+                session_name,
+                window_index,
+                pane_index,
+            }
+            | Commands::PipePane {
+                session_name,
+                window_index,
+                pane_index,
+                ..
+            } => (
+                target(session_name, Some(*window_index), Some(*pane_index)),
+                None,
+            ),
+            Commands::AttachSession { session_name, .. }
+            | Commands::AttachView { session_name, .. }
+            | Commands::StopSession { session_name }
+            | Commands::RenameSession { session_name, .. }
+            | Commands::SetEnvironment { session_name, .. }
+            | Commands::SetHook { session_name, .. } => (target(session_name, None, None), None),
+        };
+
+        PlanStep {
+            kind: self.kind(),
+            name: None,
+            target,
+            root,
+            args: self.args().unwrap_or_default(),
+        }
+    }
+
+    /// The type of the command, used for [`PlanStep`].
+    fn kind(&self) -> &'static str {
+        match self {
+            Commands::Server { .. } => "start-server",
+            Commands::ProjectEvent { .. } => "hook",
+            Commands::Session { .. } => "new-session",
+            Commands::SendKeys { .. } => "send-keys",
+            Commands::NewWindow { .. } => "new-window",
+            Commands::LinkWindow { .. } => "link-window",
+            Commands::SplitWindow { .. } => "split-window",
+            Commands::SelectLayout { .. } => "select-layout",
+            Commands::SelectWindow { .. } => "select-window",
+            Commands::SetWindowOption { .. } => "set-option",
+            Commands::SelectPane { .. } => "select-pane",
+            Commands::PipePane { .. } => "pipe-pane",
+            Commands::AttachSession { .. } => "attach-session",
+            Commands::AttachView { .. } => "new-session",
+            Commands::StopSession { .. } => "kill-session",
+            Commands::RenameSession { .. } => "rename-session",
+            Commands::SetEnvironment { .. } => "set-environment",
+            Commands::SetHook { .. } => "set-hook",
+        }
+    }
+
+    /// Formats the command as lines of a tmux configuration file, which can
+    /// be used with `tmux source-file`.
+    ///
+    /// Hooks are run with `run-shell`, and attaching is left as a comment,
+    /// since it can't be done from a configuration file.
+    pub fn tmux_conf(&self) -> String {
+        match self {
+            Commands::Server { project_name, .. } => {
+                format!("# {} {project_name:?} project", crate_name!())
+            }
+            Commands::ProjectEvent {
+                hook_name,
+                on_event,
+            } => {
+                let mut lines = vec![format!("\n# Run {hook_name} command(s)")];
+                if let Some(commands) = on_event {
+                    lines.extend(commands.iter().map(|command| {
+                        format!("run-shell {}", quote(&join_args(&hook_args(command))))
+                    }));
+                }
+                lines.join("\n")
+            }
+            Commands::AttachSession { session_name, .. } => {
+                format!(
+                    "\n# Attach with: {} attach-session -t {}",
+                    tmux_prefix(),
+                    quote(session_name)
+                )
+            }
+            Commands::AttachView { .. } => {
+                format!(
+                    "\n# Attach with: {} {}",
+                    tmux_prefix(),
+                    join_args(&self.args().unwrap_or_default())
+                )
+            }
+            _ => join_args(&self.args().unwrap_or_default()),
+        }
+    }
+
     fn fmt_server_command(
         f: &mut fmt::Formatter<'_>,
         project_name: &'a str,
@@ -97,7 +627,10 @@ impl<'a> Commands<'a> {
     ) -> fmt::Result {
         let shebang = env::var("SHELL").map(|x| format!("#!{x}")).ok();
         let cd_command = match project_root {
-            Some(project_root) => format!("\ncd {project_root}"),
+            Some(project_root) => match shellexpand::full(project_root) {
+                Ok(expanded) => format!("\ncd {}", quote(&expanded)),
+                Err(_) => format!("\ncd {}", quote_path(project_root)),
+            },
             None => "".into(),
         };
 
@@ -105,161 +638,60 @@ impl<'a> Commands<'a> {
             f,
             "{}\n\
              #\n\
-             # {} {} project\n\n\
+             # {} {:?} project\n\n\
              {} start-server\
              {}",
             shebang.unwrap_or_else(|| "".into()),
             crate_name!(),
             project_name,
-            TMUX_BIN,
+            tmux_prefix(),
             cd_command
         )
     }
 
     fn fmt_project_command(
         f: &mut fmt::Formatter<'_>,
-        event_name: &'a str,
+        hook_name: &'a str,
         on_event: &'a Option<Vec<String>>,
     ) -> fmt::Result {
-        let commands = on_event.as_ref().map_or(String::from(""), |v| v.join("\n"));
-        write!(f, "\n# Run on_project_{event_name} command(s)\n{commands}")
-    }
-
-    fn fmt_session_command(
-        f: &mut fmt::Formatter<'_>,
-        project_name: &'a str,
-        first_window_name: Option<&'a str>,
-    ) -> fmt::Result {
-        let window_param = first_window_name.map_or("".into(), |n| format!(" -n {n}"));
-        write!(
-            f,
-            "\n# Create new session and first window\n\
-            TMUX= {TMUX_BIN} new-session -d -s {project_name}{window_param}"
-        )
-    }
-
-    fn fmt_send_keys(
-        f: &mut fmt::Formatter,
-        command: &str,
-        session_name: &str,
-        window_index: usize,
-        pane_index: Option<usize>,
-        comment: Option<&str>,
-    ) -> fmt::Result {
-        let formatted_pane_index = pane_index.map_or("".into(), |idx| format!(".{idx}"));
-        let comment = comment.map_or("".into(), |c| format!("\n# {c}\n"));
-        let escaped = shell_escape::escape(command.into());
-        write!(
-            f,
-            "{comment}{TMUX_BIN} send-keys -t {session_name}:{window_index}{formatted_pane_index} {escaped} C-m"
-        )
-    }
-
-    fn get_cd_root_flag(project_root: &Option<String>) -> String {
-        match project_root {
-            Some(dir) => format!(" -c {dir}"),
-            None => "".into(),
-        }
-    }
-
-    fn fmt_new_window(
-        f: &mut fmt::Formatter,
-        session_name: &str,
-        window_name: &str,
-        window_index: usize,
-        window_root: &Option<String>,
-    ) -> fmt::Result {
-        let cd_root = Commands::get_cd_root_flag(window_root);
-        write!(
-            f,
-            "\n# Create \"{window_name}\" window \n{TMUX_BIN} new-window{cd_root} -k -t {session_name}:{window_index} -n {window_name}"
-        )
-    }
-
-    fn fmt_split_window(
-        f: &mut fmt::Formatter,
-        session_name: &str,
-        window_index: usize,
-        window_root: &Option<String>,
-    ) -> Result<(), fmt::Error> {
-        let cd_root = Commands::get_cd_root_flag(window_root);
-        write!(
-            f,
-            "{TMUX_BIN} splitw{cd_root} -t {session_name}:{window_index}"
-        )
-    }
-
-    fn fmt_select_layout(
-        f: &mut fmt::Formatter,
-        session_name: &str,
-        window_index: usize,
-        layout: &str,
-    ) -> Result<(), fmt::Error> {
-        write!(
-            f,
-            "{TMUX_BIN} select-layout -t {session_name}:{window_index} {layout}"
-        )
-    }
-
-    fn fmt_select_window(
-        f: &mut fmt::Formatter,
-        session_name: &str,
-        window_index: usize,
-    ) -> Result<(), fmt::Error> {
-        write!(
-            f,
-            "{TMUX_BIN} select-window -t {session_name}:{window_index}"
-        )
-    }
-
-    fn fmt_select_pane(
-        f: &mut fmt::Formatter,
-        session_name: &str,
-        window_index: usize,
-        pane_index: usize,
-    ) -> Result<(), fmt::Error> {
-        write!(
-            f,
-            "{TMUX_BIN} select-pane -t {session_name}:{window_index}.{pane_index}"
-        )
+        let commands = on_event.as_ref().map_or(String::from(""), |v| {
+            v.iter()
+                .map(|command| join_args(&hook_args(command)))
+                .collect::<Vec<_>>()
+                .join("\n")
+        });
+        write!(f, "\n# Run {hook_name} command(s)\n{commands}")
     }
 
-    fn fmt_attach_session(f: &mut fmt::Formatter, session_name: &str) -> Result<(), fmt::Error> {
-        write!(
-            f,
-            "\nif [ -z \"$TMUX\" ]; then\n  {TMUX_BIN} -u attach-session -t {session_name}\n\
-            else\n  {TMUX_BIN} -u switch-client -t {session_name}\nfi"
-        )
+    fn fmt_tmux_command(f: &mut fmt::Formatter, args: &[String]) -> fmt::Result {
+        write!(f, "{} {}", tmux_prefix(), join_args(args))
     }
 
-    fn fmt_stop_session(f: &mut fmt::Formatter, session_name: &str) -> Result<(), fmt::Error> {
-        write!(f, "{TMUX_BIN} kill-session -t {session_name}")
+    fn get_pipe_command(log_file: &Path) -> String {
+        format!("cat >> {}", quote(&log_file.to_string_lossy()))
     }
 
-    fn fmt_set_hook(
+    /// Formats [`Commands::AttachSession`] for both attaching from outside
+    /// tmux and switching inside it. Inside tmux the script doesn't detach
+    /// the other clients, `run` does (see [`Commands::detach_other_clients`]).
+    fn fmt_attach_session(
         f: &mut fmt::Formatter,
+        args: &[String],
         session_name: &str,
-        hook_name: &str,
-        hook_command: &str,
-    ) -> Result<(), fmt::Error> {
-        write!(
-            f,
-            "{TMUX_BIN} set-hook -t {session_name} {hook_name} \"{hook_command}\""
-        )
-    }
-
-    fn fmt_set_window_option(
-        f: &mut fmt::Formatter,
-        session_name: &str,
-        window_index: usize,
-        option_name: &str,
-        value: &str,
+        read_only: bool,
     ) -> Result<(), fmt::Error> {
+        let mut switch_args: Vec<String> = ["-u", "switch-client", "-t", session_name]
+            .map(String::from)
+            .into();
+        if read_only {
+            switch_args.extend([";", "refresh-client", "-f", "read-only"].map(String::from));
+        }
         write!(
             f,
-            "{TMUX_BIN} set-option -w -t {session_name}:{window_index} {} {}",
-            shell_escape::escape(option_name.into()),
-            shell_escape::escape(value.into())
+            "\nif [ -z \"$TMUX\" ]; then\n  {tmux} {}\nelse\n  {tmux} {}\nfi",
+            join_args(args),
+            join_args(&switch_args),
+            tmux = tmux_prefix(),
         )
     }
 
@@ -271,63 +703,151 @@ impl<'a> Commands<'a> {
                 project_root,
             } => Commands::run_server_command(project_root),
             Commands::ProjectEvent {
-                event_name: _,
+                hook_name: _,
                 on_event,
             } => Commands::run_project_event(on_event),
-            Commands::Session {
-                project_name,
-                first_window_name,
-            } => Commands::run_session_command(project_name, first_window_name),
-            Commands::SendKeys {
-                command,
+            Commands::AttachSession {
                 session_name,
-                window_index,
-                pane_index,
-                comment: _,
-            } => Commands::run_send_keys(command, session_name, *window_index, pane_index),
-            Commands::NewWindow {
+                read_only,
+                detach_others,
+                client: None,
+            } => Commands::run_attach_session(session_name, *read_only, *detach_others),
+            Commands::AttachSession {
                 session_name,
-                window_name,
+                detach_others,
+                client: Some(client),
+                ..
+            } => {
+                let args = self.run_args()?.unwrap_or_default();
+                if !tmux_command().args(args).status()?.success() {
+                    return Err(TmuxError::Message(self.error_message()));
+                }
+                if *detach_others {
+                    Commands::detach_other_clients(session_name, client)?;
+                }
+                Ok(())
+            }
+            Commands::AttachView {
+                session_name,
+                view_name,
                 window_index,
-                window_root,
-            } => Commands::run_new_window(session_name, window_name, *window_index, window_root),
+                client,
+            } => {
+                let inside_tmux = env::var("TMUX").is_ok();
+                let args = Commands::view_args(
+                    session_name,
+                    view_name,
+                    *window_index,
+                    inside_tmux,
+                    *client,
+                );
+                let res = tmux_command().args(args).status()?;
+                if res.success() {
+                    Ok(())
+                } else {
+                    Err(TmuxError::Message(self.error_message()))
+                }
+            }
+            _ => {
+                if let Commands::PipePane { log_file, .. } = self {
+                    if let Some(log_dir) = log_file.parent() {
+                        create_dir_all(log_dir)?;
+                    }
+                }
+                let args = self.run_args()?.unwrap_or_default();
+                let mut cmd = tmux_command();
+                if let Commands::Session { .. } = self {
+                    cmd.env_remove("TMUX");
+                }
+                let res = cmd.args(args).status()?;
+
+                if res.success() {
+                    Ok(())
+                } else {
+                    Err(TmuxError::Message(self.error_message()))
+                }
+            }
+        }
+    }
+
+    /// Helper returning the error message for a failed tmux command.
+    fn error_message(&self) -> String {
+        match self {
+            Commands::Session { .. } => "Cannot start session".into(),
+            Commands::SendKeys { command, .. } => format!("Cannot run send-keys for {command}"),
+            Commands::NewWindow { window_name, .. } => {
+                format!("Cannot create window {window_name}")
+            }
+            Commands::LinkWindow { source, .. } => format!("Cannot link window {source}"),
             Commands::SplitWindow {
                 session_name,
                 window_index,
-                window_root,
-            } => Commands::run_split_window(session_name, *window_index, window_root),
+                ..
+            } => format!("Cannot split window {session_name}:{window_index}"),
             Commands::SelectLayout {
                 session_name,
                 window_index,
                 layout,
-            } => Commands::run_select_layout(session_name, *window_index, layout),
+            } => format!("Cannot select layout {layout} for window {session_name}:{window_index}"),
             Commands::SelectWindow {
                 session_name,
                 window_index,
-            } => Commands::run_select_window(session_name, *window_index),
+            } => format!("Cannot select window {session_name}:{window_index}"),
             Commands::SelectPane {
                 session_name,
                 window_index,
                 pane_index,
-            } => Commands::run_select_pane(session_name, *window_index, *pane_index),
-            Commands::AttachSession { session_name } => Commands::run_attach_session(session_name),
-            Commands::StopSession { session_name } => Commands::run_stop_session(session_name),
+            } => format!("Cannot select pane {session_name}:{window_index}.{pane_index}"),
+            Commands::PipePane {
+                session_name,
+                window_index,
+                pane_index,
+                log_file,
+            } => format!(
+                "Cannot pipe pane {session_name}:{window_index}.{pane_index} to {}",
+                log_file.display()
+            ),
+            Commands::StopSession { session_name } => {
+                format!("Cannot kill session {session_name}")
+            }
+            Commands::RenameSession {
+                session_name,
+                new_name,
+            } => format!("Cannot rename session {session_name} to {new_name}"),
+            Commands::SetEnvironment {
+                session_name, name, ..
+            } => format!("Cannot set environment variable {name} for session {session_name}"),
             Commands::SetHook {
                 session_name,
                 hook_name,
-                hook_command,
-            } => Commands::run_set_hook(session_name, hook_name, hook_command),
+                ..
+            } => format!("Cannot set {hook_name} hook for session {session_name}"),
             Commands::SetWindowOption {
                 session_name,
                 window_index,
                 option_name,
                 value,
-            } => Commands::run_set_window_option(session_name, *window_index, option_name, value),
+            } => format!(
+                "Cannot set window option {option_name} = {value} for {session_name}:{window_index}"
+            ),
+            Commands::AttachSession {
+                session_name,
+                client: Some(client),
+                ..
+            } => format!("Cannot switch client {client} to session {session_name}"),
+            Commands::Server { .. }
+            | Commands::ProjectEvent { .. }
+            | Commands::AttachSession { .. } => format!("Cannot run {}", self.kind()),
+            Commands::AttachView {
+                session_name,
+                view_name,
+                ..
+            } => format!("Cannot attach to view {view_name} of session {session_name}"),
         }
     }
 
     fn run_server_command(project_root: &'a Option<String>) -> Result<(), TmuxError> {
-        Command::new(TMUX_BIN).arg("start-server").status()?;
+        tmux_command().arg("start-server").status()?;
         if let Some(root_dir) = project_root {
             set_current_dir(shellexpand::full(root_dir)?.as_ref())?;
         }
@@ -337,10 +857,10 @@ impl<'a> Commands<'a> {
     fn run_project_event(on_event: &Option<Vec<String>>) -> Result<(), TmuxError> {
         if let Some(commands) = on_event {
             for command in commands {
-                let mut parts = Shlex::new(command);
+                let mut parts = hook_args(command).into_iter();
                 let cmd_opt = parts.next();
                 if let Some(cmd) = cmd_opt {
-                    let res = Command::new(cmd).args(parts.collect::<Vec<_>>()).status();
+                    let res = Command::new(cmd).args(parts).status();
                     if res.is_err() {
                         eprintln!("Error executing command {command}");
                     }
@@ -350,290 +870,410 @@ impl<'a> Commands<'a> {
         Ok(())
     }
 
-    fn run_session_command(
-        project_name: &str,
-        first_window_name: &Option<&str>,
-    ) -> Result<(), TmuxError> {
-        let mut session_args = vec!["new-session", "-d", "-s", project_name];
-        if let Some(name) = first_window_name {
-            session_args.push("-n");
-            session_args.push(name);
-        }
-        let res = Command::new(TMUX_BIN)
-            .env_remove("TMUX")
-            .args(session_args)
-            .status()?;
-
-        if res.success() {
-            Ok(())
-        } else {
-            Err(TmuxError::Message("Cannot start session".to_string()))
+    /// Helper returning the arguments of [`Commands::AttachSession`] with a
+    /// client, switching the client to the session.
+    fn client_args(session_name: &str, client: &str, read_only: bool) -> Vec<String> {
+        let mut args: Vec<String> = ["-u", "switch-client", "-c", client, "-t", session_name]
+            .map(String::from)
+            .into();
+        if read_only {
+            args.extend([";", "refresh-client", "-t", client, "-f", "read-only"].map(String::from));
         }
+        args
     }
 
-    fn run_send_keys(
-        command: &String,
-        session_name: &str,
-        window_index: usize,
-        pane_index: &Option<usize>,
-    ) -> Result<(), TmuxError> {
-        let target_name = if let Some(pane_idx) = pane_index {
-            format!("{session_name}:{window_index}.{pane_idx}")
-        } else {
-            format!("{session_name}:{window_index}")
-        };
-        let args = ["send-keys", "-t", &target_name, command, "C-m"];
-        let res = Command::new(TMUX_BIN).args(args).status()?;
-
-        if res.success() {
-            Ok(())
-        } else {
-            Err(TmuxError::Message(format!(
-                "Cannot run send-keys for {command}"
-            )))
+    /// Helper detaching the clients attached to the session, other than the
+    /// `client`.
+    fn detach_other_clients(session_name: &str, client: &str) -> Result<(), TmuxError> {
+        let output = tmux_command()
+            .args(["list-clients", "-t", session_name, "-F", "#{client_name}"])
+            .output()?;
+        for other in String::from_utf8_lossy(&output.stdout).lines() {
+            if other != client {
+                tmux_command()
+                    .args(["detach-client", "-t", other])
+                    .status()?;
+            }
         }
+        Ok(())
     }
 
-    fn run_new_window(
+    /// Helper returning the arguments of [`Commands::AttachView`], a list of
+    /// tmux commands. Inside tmux, or with a client, the view is created
+    /// detached and switched to, since new sessions can't be nested.
+    fn view_args(
         session_name: &str,
-        window_name: &str,
-        window_index: usize,
-        window_root: &Option<String>,
-    ) -> Result<(), TmuxError> {
-        let target_name = format!("{session_name}:{window_index}");
-        let expanded: String;
-        let mut args = vec!["new-window", "-k", "-t", &target_name, "-n", window_name];
-        if let Some(root_dir) = window_root {
-            args.push("-c");
-            expanded = shellexpand::full(root_dir)?.to_string();
-            args.push(&expanded);
+        view_name: &str,
+        window_index: Option<usize>,
+        inside_tmux: bool,
+        client: Option<&str>,
+    ) -> Vec<String> {
+        let switch = inside_tmux || client.is_some();
+        let mut args: Vec<String> = vec!["-u".into(), "new-session".into()];
+        if switch {
+            args.push("-d".into());
         }
-        let res = Command::new(TMUX_BIN).args(args).status()?;
-
-        if res.success() {
-            Ok(())
-        } else {
-            Err(TmuxError::Message(format!(
-                "Cannot create window {window_name}"
-            )))
-        }
-    }
-
-    fn run_split_window(
-        session_name: &str,
-        window_index: usize,
-        window_root: &Option<String>,
-    ) -> Result<(), TmuxError> {
-        let target_name = format!("{session_name}:{window_index}");
-        let mut args = vec!["splitw", "-t", &target_name];
-        let expanded: String;
-        if let Some(root_dir) = window_root {
-            args.push("-c");
-            expanded = shellexpand::full(root_dir)?.to_string();
-            args.push(&expanded);
+        args.extend([
+            "-t".into(),
+            session_name.to_string(),
+            "-s".into(),
+            view_name.to_string(),
+        ]);
+        if let Some(window_index) = window_index {
+            args.extend([
+                ";".into(),
+                "select-window".into(),
+                "-t".into(),
+                format!("{view_name}:{window_index}"),
+            ]);
         }
-        let res = Command::new(TMUX_BIN).args(args).status()?;
-
-        if res.success() {
-            Ok(())
-        } else {
-            Err(TmuxError::Message(format!(
-                "Cannot split window {target_name}"
-            )))
+        if switch {
+            args.extend([";".into(), "switch-client".into()]);
+            if let Some(client) = client {
+                args.extend(["-c".into(), client.to_string()]);
+            }
+            args.extend(["-t".into(), view_name.to_string()]);
         }
+        args.extend([
+            ";".into(),
+            "set-option".into(),
+            "-t".into(),
+            view_name.to_string(),
+            "destroy-unattached".into(),
+            "on".into(),
+        ]);
+        args
     }
 
-    fn run_select_layout(
+    fn run_attach_session(
         session_name: &str,
-        window_index: usize,
-        layout: &str,
+        read_only: bool,
+        detach_others: bool,
     ) -> Result<(), TmuxError> {
-        let target_name = format!("{session_name}:{window_index}");
-        let args = vec!["select-layout", "-t", &target_name, layout];
-        let res = Command::new(TMUX_BIN).args(args).status()?;
-
-        if res.success() {
-            Ok(())
-        } else {
-            Err(TmuxError::Message(format!(
-                "Cannot select layout {layout} for window {target_name}"
-            )))
-        }
-    }
-
-    fn run_select_window(session_name: &str, window_index: usize) -> Result<(), TmuxError> {
-        let target_name = format!("{session_name}:{window_index}");
-        let args = vec!["select-window", "-t", &target_name];
-        let res = Command::new(TMUX_BIN).args(args).status()?;
-
-        if res.success() {
-            Ok(())
-        } else {
-            Err(TmuxError::Message(format!(
-                "Cannot select window {target_name}"
-            )))
-        }
-    }
-
-    fn run_select_pane(
-        session_name: &str,
-        window_index: usize,
-        pane_index: usize,
-    ) -> Result<(), TmuxError> {
-        let target_name = format!("{session_name}:{window_index}.{pane_index}");
-        let args = vec!["select-pane", "-t", &target_name];
-        let res = Command::new(TMUX_BIN).args(args).status()?;
-
-        if res.success() {
-            Ok(())
-        } else {
-            Err(TmuxError::Message(format!(
-                "Cannot select pane {target_name}"
-            )))
-        }
-    }
-
-    fn run_attach_session(session_name: &str) -> Result<(), TmuxError> {
-        let param = if env::var("TMUX").is_ok() {
-            "switch-client"
-        } else {
-            "attach-session"
-        };
-        let args = ["-u", param, "-t", session_name];
-        let res = Command::new(TMUX_BIN).args(args).status()?;
-        if res.success() {
-            Ok(())
-        } else {
-            Err(TmuxError::Message(format!(
-                "Cannot {param} to session {session_name}"
-            )))
+        if env::var("TMUX").is_ok() {
+            // Switch the current client, like an explicit one
+            let client = TmuxServer::get()
+                .current_client()
+                .ok_or_else(|| TmuxError::Message("Cannot get the current client".into()))?;
+            return Commands::AttachSession {
+                session_name,
+                read_only,
+                detach_others,
+                client: Some(&client),
+            }
+            .run();
         }
-    }
-
-    fn run_stop_session(session_name: &str) -> Result<(), TmuxError> {
-        let args = ["kill-session", "-t", session_name];
-        let res = Command::new(TMUX_BIN).args(args).status()?;
-        if res.success() {
-            Ok(())
-        } else {
-            Err(TmuxError::Message(format!(
-                "Cannot kill session {session_name}"
-            )))
+        let mut args = vec!["-u", "attach-session"];
+        if read_only {
+            args.push("-r");
         }
-    }
-
-    fn run_set_hook(
-        session_name: &str,
-        hook_name: &str,
-        hook_command: &str,
-    ) -> Result<(), TmuxError> {
-        let args = ["set-hook", "-t", session_name, hook_name, hook_command];
-        let res = Command::new(TMUX_BIN).args(args).status()?;
-        if res.success() {
-            Ok(())
-        } else {
-            Err(TmuxError::Message(format!(
-                "Cannot set {hook_name} hook for session {session_name}"
-            )))
+        if detach_others {
+            args.push("-d");
         }
-    }
-
-    fn run_set_window_option(
-        session_name: &str,
-        window_index: usize,
-        option_name: &str,
-        value: &str,
-    ) -> Result<(), TmuxError> {
-        let args = [
-            "set-option",
-            "-w",
-            "-t",
-            &format!("{session_name}:{window_index}"),
-            option_name,
-            value,
-        ];
-        let res = Command::new(TMUX_BIN).args(args).status()?;
+        args.extend(["-t", session_name]);
+        let res = tmux_command().args(args).status()?;
         if res.success() {
             Ok(())
         } else {
             Err(TmuxError::Message(format!(
-                "Cannot set window option {option_name} = {value } for {session_name}:{window_index}"
+                "Cannot attach-session to session {session_name}"
             )))
         }
     }
 }
 
 impl<'a> fmt::Display for Commands<'a> {
+    /// Formats the command as a shell script, with the arguments used when
+    /// running it (the paths expanded), falling back to the ones specified in
+    /// the project if they can't be expanded.
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let args = match self.run_args() {
+            Ok(args) => args,
+            Err(_) => self.args(),
+        }
+        .unwrap_or_default();
         match self {
             Commands::Server {
                 project_name,
                 project_root,
             } => Commands::fmt_server_command(f, project_name, project_root),
             Commands::ProjectEvent {
-                event_name,
+                hook_name,
                 on_event,
-            } => Commands::fmt_project_command(f, event_name, on_event),
-            Commands::Session {
-                project_name,
-                first_window_name,
-            } => Commands::fmt_session_command(f, project_name, *first_window_name),
+            } => Commands::fmt_project_command(f, hook_name, on_event),
+            Commands::Session { .. } => {
+                write!(f, "\n# Create new session and first window\nTMUX= ")?;
+                Commands::fmt_tmux_command(f, &args)
+            }
             Commands::SendKeys {
-                command,
-                session_name,
-                window_index,
-                pane_index,
-                comment,
-            } => Commands::fmt_send_keys(
-                f,
-                command,
-                session_name,
-                *window_index,
-                *pane_index,
-                comment.as_ref().map(|x| &**x),
-            ),
-            Commands::NewWindow {
-                session_name,
-                window_name,
-                window_index,
-                window_root,
-            } => Commands::fmt_new_window(f, session_name, window_name, *window_index, window_root),
-            Commands::SplitWindow {
-                session_name,
-                window_index,
-                window_root,
-            } => Commands::fmt_split_window(f, session_name, *window_index, window_root),
-            Commands::SelectLayout {
-                session_name,
-                window_index,
-                layout,
-            } => Commands::fmt_select_layout(f, session_name, *window_index, layout),
-            Commands::SelectWindow {
-                session_name,
-                window_index,
-            } => Commands::fmt_select_window(f, session_name, *window_index),
-            Commands::SelectPane {
-                session_name,
-                window_index,
-                pane_index,
-            } => Commands::fmt_select_pane(f, session_name, *window_index, *pane_index),
-            Commands::AttachSession { session_name } => {
-                Commands::fmt_attach_session(f, session_name)
+                comment: Some(comment),
+                ..
+            } => {
+                write!(f, "\n# {comment}\n")?;
+                Commands::fmt_tmux_command(f, &args)
             }
-            Commands::StopSession { session_name } => Commands::fmt_stop_session(f, session_name),
-            Commands::SetHook {
+            Commands::NewWindow { window_name, .. } => {
+                write!(f, "\n# Create {window_name:?} window\n")?;
+                Commands::fmt_tmux_command(f, &args)
+            }
+            Commands::LinkWindow { source, .. } => {
+                write!(f, "\n# Link {source:?} window\n")?;
+                Commands::fmt_tmux_command(f, &args)
+            }
+            Commands::PipePane { log_file, .. } => {
+                let log_dir = log_file.parent().unwrap_or(Path::new("."));
+                writeln!(f, "mkdir -p {}", quote(&log_dir.to_string_lossy()))?;
+                Commands::fmt_tmux_command(f, &args)
+            }
+            Commands::AttachSession {
+                client: Some(_), ..
+            }
+            | Commands::AttachView {
+                client: Some(_), ..
+            } => Commands::fmt_tmux_command(f, &args),
+            Commands::AttachSession {
                 session_name,
-                hook_name,
-                hook_command,
-            } => Commands::fmt_set_hook(f, session_name, hook_name, hook_command),
-            Commands::SetWindowOption {
+                read_only,
+                ..
+            } => Commands::fmt_attach_session(f, &args, session_name, *read_only),
+            Commands::AttachView {
                 session_name,
+                view_name,
                 window_index,
-                option_name,
-                value,
+                client: None,
             } => {
-                Commands::fmt_set_window_option(f, session_name, *window_index, option_name, value)
+                let inside_args =
+                    Commands::view_args(session_name, view_name, *window_index, true, None);
+                write!(
+                    f,
+                    "\nif [ -z \"$TMUX\" ]; then\n  {tmux} {}\nelse\n  {tmux} {}\nfi",
+                    join_args(&args),
+                    join_args(&inside_args),
+                    tmux = tmux_prefix(),
+                )
             }
+            _ => Commands::fmt_tmux_command(f, &args),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::server::TMUX_BIN;
+    use super::*;
+    use proptest::prelude::*;
+
+    /// Values with spaces, quotes and shell metacharacters.
+    fn value() -> impl Strategy<Value = String> {
+        "[a-z ~/'\"$;&|`\\\\*#-]{1,12}"
+    }
+
+    /// Parses a script line back with `shlex`, dropping the `TMUX=` prefix
+    /// and the tmux binary.
+    fn parse_tmux_line(line: &str) -> Vec<String> {
+        let mut tokens = shlex::split(line).expect("line should be valid shell");
+        if tokens.first().is_some_and(|token| token == "TMUX=") {
+            tokens.remove(0);
+        }
+        assert_eq!(tokens.remove(0), TMUX_BIN);
+        tokens
+    }
+
+    /// Returns the command lines of the formatted command, without comments,
+    /// blank lines and the `mkdir` of the logs directory.
+    fn command_lines(command: &Commands) -> Vec<String> {
+        command
+            .to_string()
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter(|line| !line.starts_with("mkdir "))
+            .map(String::from)
+            .collect()
+    }
+
+    /// Returns the arguments `run` uses, or the ones specified in the
+    /// project if a path can't be expanded (and running it fails).
+    fn expected_args(command: &Commands) -> Option<Vec<String>> {
+        command.run_args().unwrap_or_else(|_| command.args())
+    }
+
+    fn assert_roundtrip(command: &Commands) {
+        let lines = command_lines(command);
+        assert_eq!(lines.len(), 1, "{lines:?}");
+        assert_eq!(Some(parse_tmux_line(&lines[0])), expected_args(command));
+    }
+
+    proptest! {
+        #[test]
+        fn session_quoting(name in value(), window in value()) {
+            let env = BTreeMap::from([("NAME".to_string(), window.clone())]);
+            assert_roundtrip(&Commands::Session {
+                project_name: &name,
+                first_window_name: Some(&window),
+                env: Some(&env),
+            });
+            assert_roundtrip(&Commands::SetEnvironment {
+                session_name: &name,
+                name: "NAME",
+                value: &window,
+            });
+            assert_roundtrip(&Commands::RenameSession {
+                session_name: &name,
+                new_name: &window,
+            });
+        }
+
+        #[test]
+        fn send_keys_quoting(name in value(), command in value(), comment in value()) {
+            assert_roundtrip(&Commands::SendKeys {
+                command,
+                session_name: &name,
+                window_index: 1,
+                pane_index: Some(2),
+                comment: Some(comment),
+            });
+        }
+
+        #[test]
+        fn window_quoting(name in value(), window in value(), root in value(), layout in value()) {
+            assert_roundtrip(&Commands::NewWindow {
+                session_name: &name,
+                window_name: &window,
+                window_index: 1,
+                window_root: Some(root.clone()),
+            });
+            assert_roundtrip(&Commands::SplitWindow {
+                session_name: &name,
+                window_index: 1,
+                window_root: Some(root),
+            });
+            assert_roundtrip(&Commands::LinkWindow {
+                session_name: &name,
+                window_index: 1,
+                source: &window,
+            });
+            assert_roundtrip(&Commands::SelectLayout {
+                session_name: &name,
+                window_index: 1,
+                layout: &layout,
+            });
+            assert_roundtrip(&Commands::SelectWindow {
+                session_name: &name,
+                window_index: 1,
+            });
+            assert_roundtrip(&Commands::SelectPane {
+                session_name: &name,
+                window_index: 1,
+                pane_index: 0,
+            });
+        }
+
+        #[test]
+        fn option_quoting(name in value(), option in value(), option_value in value(), hook in value()) {
+            assert_roundtrip(&Commands::SetWindowOption {
+                session_name: &name,
+                window_index: 1,
+                option_name: &option,
+                value: &option_value,
+            });
+            assert_roundtrip(&Commands::SetHook {
+                session_name: &name,
+                hook_name: "client-detached",
+                hook_command: format!("run-shell {}", quote(&hook)),
+            });
+            assert_roundtrip(&Commands::StopSession { session_name: &name });
+        }
+
+        #[test]
+        fn pipe_pane_quoting(name in value(), dir in value()) {
+            assert_roundtrip(&Commands::PipePane {
+                session_name: &name,
+                window_index: 1,
+                pane_index: 0,
+                log_file: PathBuf::from(dir).join("window.0.log"),
+            });
+        }
+
+        #[test]
+        fn attach_quoting(name in value()) {
+            let command = Commands::AttachSession {
+                session_name: &name,
+                read_only: true,
+                detach_others: false,
+                client: None,
+            };
+            let lines = command_lines(&command);
+            assert_eq!(lines[0], "if [ -z \"$TMUX\" ]; then");
+            assert_eq!(Some(parse_tmux_line(&lines[1])), expected_args(&command));
+            assert_eq!(
+                parse_tmux_line(&lines[3]),
+                vec!["-u", "switch-client", "-t", name.as_str(), ";", "refresh-client", "-f", "read-only"]
+            );
+        }
+
+        #[test]
+        fn attach_client_quoting(name in value(), client in value()) {
+            assert_roundtrip(&Commands::AttachSession {
+                session_name: &name,
+                read_only: true,
+                detach_others: true,
+                client: Some(&client),
+            });
+        }
+
+        #[test]
+        fn attach_view_quoting(name in value(), view in value()) {
+            let command = Commands::AttachView {
+                session_name: &name,
+                view_name: &view,
+                window_index: Some(1),
+                client: None,
+            };
+            let lines = command_lines(&command);
+            assert_eq!(lines[0], "if [ -z \"$TMUX\" ]; then");
+            assert_eq!(Some(parse_tmux_line(&lines[1])), expected_args(&command));
+            assert_eq!(
+                parse_tmux_line(&lines[3]),
+                Commands::view_args(&name, &view, Some(1), true, None)
+            );
+        }
+
+        #[test]
+        fn server_quoting(name in value(), root in value()) {
+            let project_root = Some(root.clone());
+            let command = Commands::Server {
+                project_name: &name,
+                project_root: &project_root,
+            };
+            let lines = command_lines(&command);
+            assert_eq!(parse_tmux_line(&lines[0]), vec!["start-server"]);
+            // The directory `run` changes to
+            let dir = shellexpand::full(&root).map_or(root.clone(), |dir| dir.to_string());
+            assert_eq!(
+                shlex::split(&lines[1]),
+                Some(vec!["cd".to_string(), dir])
+            );
+        }
+
+        #[test]
+        fn hook_quoting(commands in prop::collection::vec(value(), 1..4)) {
+            let on_event = Some(commands.clone());
+            let command = Commands::ProjectEvent {
+                hook_name: "on_project_start",
+                on_event: &on_event,
+            };
+            let lines = command_lines(&command);
+            let expected: Vec<_> = commands
+                .iter()
+                .map(|command| hook_args(command))
+                .filter(|args| !args.is_empty())
+                .collect();
+            let parsed: Vec<_> = lines
+                .iter()
+                .map(|line| shlex::split(line).expect("line should be valid shell"))
+                .collect();
+            assert_eq!(parsed, expected);
         }
     }
 }
@@ -10,6 +10,12 @@ pub enum TmuxError {
     /// Generic string error message, (e.g.: _problem starting a session_).
     #[error("{0}")]
     Message(String),
+    /// A waited for pane's command exited with a non-zero status.
+    #[error("Command in pane {target} exited with status {code}")]
+    PaneExit { target: String, code: i32 },
+    /// Error serializing the commands to JSON.
+    #[error("Cannot serialize commands: {0}")]
+    Serialize(#[from] serde_json::Error),
     /// Error expanding a directory/file path.
     #[error("Can not expand path")]
     Expand(#[from] shellexpand::LookupError<std::env::VarError>),
@@ -2,9 +2,15 @@
 mod commands;
 mod error;
 mod project;
+mod server;
 mod version;
 
+pub(crate) use commands::{quote_path, run_hook};
 pub use error::TmuxError;
+pub use project::view_names;
+pub use project::AttachOptions;
+pub use project::SendOptions;
 pub use project::TmuxProject;
-pub use project::TMUX_BIN;
+pub use project::TmuxSettings;
+pub use server::TmuxServer;
 pub use version::TmuxVersion;
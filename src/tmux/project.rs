@@ -1,14 +1,30 @@
 //! Maps rusmux's [`ProjectConfig`] to tmux commands and operations.
-use super::commands::Commands;
+use super::commands::{quote, quote_path, Commands};
+use super::server::{current_command, tmux_command, tmux_prefix, TmuxServer};
 use super::TmuxError;
 use super::TmuxVersion;
 use crate::project_config::ProjectConfig;
 use crate::project_config::Window;
-use std::fmt;
+use clap::crate_name;
+use colored::Colorize;
+use regex::Regex;
+use std::fs::{File, Permissions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
 use std::process::Command;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+use std::{env, fmt};
 
-pub const TMUX_BIN: &str = "tmux";
 const READ_ERROR: &str = "Cannot get tmux version and config options";
+/// Infix of the names of a session's views, `<session>-view-<number>`.
+const VIEW_INFIX: &str = "-view-";
+/// Suffix of the name of the session holding the clients while a project is
+/// restarted from inside it.
+const HOLDING_SUFFIX: &str = "-restarting";
+/// Pane user option storing the exit status of a waited for pane.
+const EXIT_STATUS_OPTION: &str = "@rusmux-exit-status";
 
 /// Stores Tmux configuration information.
 ///
@@ -24,6 +40,13 @@ struct Tmux {
     version: TmuxVersion,
 }
 
+impl Default for Tmux {
+    /// The tmux defaults, assuming the latest version.
+    fn default() -> Self {
+        Self::new(0, 0, TmuxVersion::Max)
+    }
+}
+
 impl Tmux {
     /// Create a new `Tmux` instance with the proposed `base-index` and `pane-base-index`.
     fn new(base_index: usize, pane_base_index: usize, version: TmuxVersion) -> Self {
@@ -34,10 +57,31 @@ impl Tmux {
         }
     }
 
+    /// Create a new `Tmux` instance from the overrides, reading the missing
+    /// values from the installed tmux configuration.
+    ///
+    /// If tmux can't be run or its output can't be parsed, prints a warning
+    /// and falls back to the defaults.
+    fn from_settings(settings: &TmuxSettings) -> Self {
+        let probed = if settings.is_complete() {
+            Self::default()
+        } else {
+            Self::new_from_config().unwrap_or_else(|err| {
+                eprintln!("{}", format!("{err}, using the defaults").yellow());
+                Self::default()
+            })
+        };
+        Self::new(
+            settings.base_index.unwrap_or(probed.base_index),
+            settings.pane_base_index.unwrap_or(probed.pane_base_index),
+            settings.version.unwrap_or(probed.version),
+        )
+    }
+
     /// Create a new `Tmux` instance getting the values of `base-index` and `pane-base-index`
     /// from the installed tmux configuration.
     fn new_from_config() -> Result<Self, TmuxError> {
-        let output = Command::new(TMUX_BIN)
+        let output = tmux_command()
             .args([
                 "start",
                 ";",
@@ -54,78 +98,853 @@ impl Tmux {
                 "#{version}",
             ])
             .output()
-            .map_err(|_| TmuxError::Message(READ_ERROR.into()))?
+            .map_err(|e| {
+                TmuxError::Message(format!("Cannot run {}: {e}", TmuxServer::get().binary()))
+            })?
             .stdout;
 
-        let binding =
-            String::from_utf8(output).map_err(|_| TmuxError::Message(READ_ERROR.into()))?;
-        let lines: Vec<&str> = binding.lines().collect();
+        String::from_utf8(output)
+            .ok()
+            .and_then(|output| Self::parse_config(&output))
+            .ok_or_else(|| TmuxError::Message(READ_ERROR.into()))
+    }
 
-        let values: Vec<usize> = lines
-            .iter()
-            .take(2)
-            .map(|line| line.split(' ').nth(1).unwrap().parse::<usize>().unwrap())
-            .collect();
+    /// Parses the output of the `show` commands for the indices, followed by
+    /// the version (missing in tmux < 2.4).
+    fn parse_config(output: &str) -> Option<Self> {
+        let mut lines = output.lines();
+        let mut option = |name: &str| {
+            let (option, value) = lines.next()?.split_once(' ')?;
+            if option != name {
+                return None;
+            }
+            value.trim().parse::<usize>().ok()
+        };
+        let base_index = option("base-index")?;
+        let pane_base_index = option("pane-base-index")?;
+        Some(Self::new(base_index, pane_base_index, lines.next().into()))
+    }
+}
+
+/// Overrides of the tmux configuration options and version, which are
+/// otherwise read from the installed tmux. Setting all of them skips running
+/// tmux, e.g. to generate the commands on a machine without tmux.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct TmuxSettings {
+    /// The `base-index` option.
+    pub base_index: Option<usize>,
+    /// The `pane-base-index` option.
+    pub pane_base_index: Option<usize>,
+    /// The tmux version.
+    pub version: Option<TmuxVersion>,
+}
+
+impl TmuxSettings {
+    /// Returns `true` if all the values are set.
+    fn is_complete(&self) -> bool {
+        self.base_index.is_some() && self.pane_base_index.is_some() && self.version.is_some()
+    }
+}
 
-        match lines.len() {
-            2 => Ok(Self::new(values[0], values[1], None.into())),
-            3 => Ok(Self::new(values[0], values[1], Some(lines[2]).into())),
-            _ => Err(TmuxError::Message(READ_ERROR.into())),
+/// Controls attaching to the session once the project is started.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttachOptions {
+    /// Attach (or switch) to the session. If `false` the session is left
+    /// running detached.
+    pub attach: bool,
+    /// Attach in read-only mode (`attach-session -r`).
+    pub read_only: bool,
+    /// Detach other clients attached to the session (`attach-session -d`).
+    pub detach_others: bool,
+    /// Switch this client (e.g. `/dev/pts/3`, see `tmux list-clients`) to
+    /// the session, rather than attaching the current terminal or switching
+    /// the current client.
+    pub client: Option<String>,
+}
+
+impl Default for AttachOptions {
+    fn default() -> Self {
+        Self {
+            attach: true,
+            read_only: false,
+            detach_others: false,
+            client: None,
         }
     }
 }
 
+/// Controls sending keys to the panes of a running session.
+#[derive(Debug)]
+pub struct SendOptions {
+    /// Send the keys literally (`send-keys -l`), without looking up key names
+    /// like `C-c` or `Enter`.
+    pub literal: bool,
+    /// Press `Enter` after sending the keys.
+    pub enter: bool,
+    /// Block until the new output of the pane matches the regex.
+    pub wait_for_output: Option<Regex>,
+    /// How long to wait for the output before failing.
+    pub timeout: Duration,
+}
+
+/// How the project is started, determines the hooks and commands to run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StartMode {
+    /// The session isn't running, create it.
+    Fresh,
+    /// The session is already running, attach to it.
+    Reattach,
+    /// The session was stopped for restart, create it again and select
+    /// the previously active `(window_index, pane_index)` (if any).
+    Restart(Option<(usize, usize)>),
+}
+
+/// A tmux session of the project, its own one or one of its additional
+/// [`sessions`](ProjectConfig::sessions).
+#[derive(Debug, Clone, Copy)]
+struct TmuxSession<'a> {
+    name: &'a str,
+    /// The root of the session's windows.
+    root: &'a Option<String>,
+    windows: &'a [Window],
+}
+
 /// The tmux project, generates the required commands based on
 /// [ProjectConfig] and [Tmux] settings.
 #[derive(Debug)]
 pub struct TmuxProject<'a> {
     tmux: Tmux,
     project: &'a ProjectConfig,
+    attach: AttachOptions,
+    /// `(window_index, pane_index)` of the panes to wait for, see
+    /// [`with_wait`](Self::with_wait).
+    wait: Vec<(usize, usize)>,
 }
 
 impl<'a> TmuxProject<'a> {
-    /// Creates a new Tmux project from a [`ProjectConfig`].
-    pub fn new(project: &'a ProjectConfig) -> Result<Self, TmuxError> {
-        let tmux = Tmux::new_from_config()?;
-        Ok(TmuxProject { tmux, project })
+    /// Creates a new Tmux project from a [`ProjectConfig`], reading the tmux
+    /// settings from the installed tmux.
+    pub fn new(project: &'a ProjectConfig) -> Self {
+        Self::with_settings(project, &TmuxSettings::default())
+    }
+
+    /// Creates a new Tmux project from a [`ProjectConfig`], overriding the
+    /// tmux settings, see [`TmuxSettings`].
+    pub fn with_settings(project: &'a ProjectConfig, settings: &TmuxSettings) -> Self {
+        TmuxProject {
+            tmux: Tmux::from_settings(settings),
+            project,
+            attach: AttachOptions {
+                attach: project.attach.unwrap_or(true),
+                ..Default::default()
+            },
+            wait: vec![],
+        }
+    }
+
+    /// Overrides the attach options (by default taken from the project's
+    /// `attach` setting).
+    pub fn with_attach(mut self, attach: AttachOptions) -> Self {
+        self.attach = attach;
+        self
+    }
+
+    /// Waits for the commands of the `<window>[.<pane>]` targets to exit
+    /// once the session is created, see [`resolve_target`](Self::resolve_target).
+    ///
+    /// The last command sent to each of these panes is followed by one storing
+    /// its exit status and signaling `tmux wait-for`, hence requires a POSIX
+    /// compatible shell and tmux >= 3.0.
+    pub fn with_wait(mut self, targets: &[String]) -> Result<Self, TmuxError> {
+        self.wait = targets
+            .iter()
+            .map(|target| self.resolve_target(target))
+            .collect::<Result<_, _>>()?;
+        Ok(self)
+    }
+
+    /// Helper returning the position and the definition of a window, by its
+    /// name or index (taking `base-index` into account).
+    fn find_window(&self, name: &str) -> Option<(usize, &Window)> {
+        let windows = self.project.windows.as_deref().unwrap_or_default();
+        windows
+            .iter()
+            .position(|w| w.name == name)
+            .or_else(|| {
+                name.parse::<usize>()
+                    .ok()
+                    .and_then(|idx| idx.checked_sub(self.tmux.base_index))
+                    .filter(|idx| *idx < windows.len())
+            })
+            .map(|idx| (idx, &windows[idx]))
+    }
+
+    /// Resolves a `<window>[.<pane>]` target to tmux's `(window_index, pane_index)`.
+    ///
+    /// The window can be the name of a window in the project file, or its
+    /// index (taking `base-index` into account). The pane is the index of the
+    /// pane in the window (taking `pane-base-index` into account), and
+    /// defaults to the first one.
+    pub fn resolve_target(&self, target: &str) -> Result<(usize, usize), TmuxError> {
+        let (position, window, pane_index) = match self.find_window(target) {
+            Some((position, window)) => (position, window, self.tmux.pane_base_index),
+            None => {
+                let (window_name, pane) = target
+                    .rsplit_once('.')
+                    .and_then(|(name, pane)| Some((name, pane.parse::<usize>().ok()?)))
+                    .ok_or_else(|| TmuxError::Message(format!("Unknown window {target}")))?;
+                let (position, window) = self
+                    .find_window(window_name)
+                    .ok_or_else(|| TmuxError::Message(format!("Unknown window {window_name}")))?;
+                (position, window, pane)
+            }
+        };
+
+        let pane_exists = pane_index
+            .checked_sub(self.tmux.pane_base_index)
+            .is_some_and(|idx| idx < window.panes.len());
+        if !pane_exists {
+            return Err(TmuxError::Message(format!(
+                "Unknown pane {pane_index} in window {}",
+                window.name
+            )));
+        }
+
+        Ok((position + self.tmux.base_index, pane_index))
     }
 
-    /// Gets the list of commands for the project, and runs them:
+    /// Gets the list of commands for the project, and runs them.
     ///
-    /// - Commands to create the session if not running already.
-    /// - Commands to attach to an existing session.
+    /// Follows tmuxinator's hooks ordering:
+    ///
+    /// - `on_project_start` runs every time.
+    /// - `pre`, `on_project_first_start` and the session creation commands
+    ///   run only if the session isn't running already.
+    /// - `on_project_restart` runs only if the session is running already.
+    /// - Attach to the session (unless disabled), and once detached run
+    ///   `post` and `on_project_exit`.
+    ///
+    /// Finally, waits for the panes set with [`with_wait`](Self::with_wait),
+    /// failing with [`TmuxError::PaneExit`] if one of them exited with a
+    /// non-zero status.
     pub fn run(&self) -> Result<(), TmuxError> {
-        let cmds = if self.session_exists()? {
-            vec![self.get_attach_session_command()]
+        let session_exists = self.session_exists()?;
+        if session_exists && !self.wait.is_empty() {
+            return Err(TmuxError::Message(format!(
+                "Session {} is already running, can not wait for its panes",
+                self.project.project_name
+            )));
+        }
+
+        let mode = if session_exists {
+            StartMode::Reattach
         } else {
-            self.get_commands()
+            StartMode::Fresh
         };
+        let cmds = self.get_commands(mode);
         for cmd in cmds {
             cmd.run()?;
         }
+        self.wait_for_panes()
+    }
+
+    /// Restarts the project's session.
+    ///
+    /// Stops the session (running `on_project_stop`), and starts it again
+    /// running `on_project_restart` instead of `on_project_first_start`.
+    /// The window and pane which were active are selected again (by the
+    /// window's name), also once the layouts are applied on attaching.
+    ///
+    /// From inside one of the project's sessions, which would be killed with
+    /// the pane running rusmux, the client is first moved to a temporary
+    /// session, and the restart is run again in the background (by
+    /// `run-shell -b`). Once the sessions are created, the clients are
+    /// switched back, and the temporary session is killed.
+    pub fn restart(&self) -> Result<(), TmuxError> {
+        let server = TmuxServer::get();
+        if let Some(session_name) = server.current_session() {
+            if self.is_own_session(&session_name) {
+                return self.restart_in_background();
+            }
+        }
+
+        let mut active = None;
+        if self.session_exists()? {
+            active = self.get_active_target()?;
+            self.run_stop_session_commands()?;
+        }
+
+        let holding = self.holding_session_name();
+        let held = server.session_names().contains(&holding);
+        for cmd in self.get_commands(StartMode::Restart(active)) {
+            // The held clients are switched back instead
+            if held && matches!(cmd, Commands::AttachSession { .. }) {
+                continue;
+            }
+            cmd.run()?;
+            if let Commands::SelectWindow { window_index, .. } = cmd {
+                self.select_after_layouts(window_index)?;
+            }
+        }
+        if held {
+            self.release_held_clients(&holding)?;
+        }
         Ok(())
     }
 
-    /// Stops the project's session, and run `on_project_stop`
-    /// (if specified).
-    pub fn stop(&self) -> Result<(), TmuxError> {
-        let cmds = self.get_stop_session_commands();
-        for cmd in cmds {
+    /// Moves the current client to the holding session, and runs the current
+    /// command again in the background, see [`TmuxProject::restart`].
+    fn restart_in_background(&self) -> Result<(), TmuxError> {
+        let client = TmuxServer::get()
+            .current_client()
+            .ok_or_else(|| TmuxError::Message("Cannot get the current client".into()))?;
+        let dir = env::current_dir()?;
+        let args = restart_in_background_args(
+            &self.holding_session_name(),
+            &client,
+            &dir.to_string_lossy(),
+            &current_command()?,
+        );
+        let res = tmux_command().args(args).output()?;
+        if !res.status.success() {
+            return Err(TmuxError::Message(format!(
+                "Cannot restart session {} in the background: {}",
+                self.project.project_name,
+                String::from_utf8_lossy(&res.stderr).trim_end()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Switches the clients of the holding session back to the project's
+    /// session, and kills the holding session.
+    fn release_held_clients(&self, holding: &str) -> Result<(), TmuxError> {
+        let output = tmux_command()
+            .args(["list-clients", "-t", holding, "-F", "#{client_name}"])
+            .output()?;
+        for client in String::from_utf8_lossy(&output.stdout).lines() {
+            tmux_command()
+                .args(["switch-client", "-c", client, "-t"])
+                .arg(&self.project.project_name)
+                .output()?;
+        }
+        tmux_command()
+            .args(["kill-session", "-t", holding])
+            .output()?;
+        Ok(())
+    }
+
+    /// Returns the name of the session holding the clients while the
+    /// project's sessions are restarted from inside them.
+    fn holding_session_name(&self) -> String {
+        format!("{}{HOLDING_SUFFIX}", self.project.project_name)
+    }
+
+    /// Checks if a session is one of the project's sessions or views.
+    fn is_own_session(&self, session_name: &str) -> bool {
+        self.sessions()
+            .iter()
+            .any(|session| session.name == session_name)
+            || view_number(&self.project.project_name, session_name).is_some()
+    }
+
+    /// Re-creates a single window of the running session from its definition
+    /// in the project file, leaving the other windows untouched.
+    pub fn restart_window(&self, window_name: &str) -> Result<(), TmuxError> {
+        if !self.session_exists()? {
+            return Err(TmuxError::Message(format!(
+                "Session {} is not running",
+                self.project.project_name
+            )));
+        }
+
+        for cmd in self.get_restart_window_commands(window_name)? {
             cmd.run()?;
         }
         Ok(())
     }
 
-    /// Helper returning the [`Commands`] for creating the project's session.
-    fn get_commands(&self) -> Vec<Commands<'_>> {
+    /// Runs `on_project_stop` (if specified), and stops the project's
+    /// sessions.
+    pub fn stop(&self) -> Result<(), TmuxError> {
+        self.run_stop_session_commands()
+    }
+
+    /// Attaches (or switches) to the project's running session, with the
+    /// [`AttachOptions`] set with [`with_attach`](Self::with_attach).
+    pub fn attach(&self) -> Result<(), TmuxError> {
+        self.ensure_running()?;
+        self.get_attach_session_command().run()
+    }
+
+    /// Selects a window (name or index) of the project's running session,
+    /// and optionally a pane of it, see [`resolve_target`](Self::resolve_target).
+    pub fn select_window(&self, window: &str, pane: Option<usize>) -> Result<(), TmuxError> {
+        self.ensure_running()?;
+        let (window_index, pane_index) = self.resolve_selection(window, pane)?;
+        let session_name = &self.project.project_name;
+        Commands::SelectWindow {
+            session_name,
+            window_index,
+        }
+        .run()?;
+        if let Some(pane_index) = pane_index {
+            Commands::SelectPane {
+                session_name,
+                window_index,
+                pane_index,
+            }
+            .run()?;
+        }
+        self.select_after_layouts(window_index)
+    }
+
+    /// The layouts hook (see `get_layout_hooks_command`) selects the windows
+    /// in turn once the session is first attached to, selects the window
+    /// again after it.
+    fn select_after_layouts(&self, window_index: usize) -> Result<(), TmuxError> {
+        let session_name = &self.project.project_name;
+        let hook = tmux_command()
+            .args(["show-hooks", "-t", session_name, "client-session-changed"])
+            .output()?;
+        if !hook.stdout.is_empty() {
+            let target = format!("{session_name}:{window_index}");
+            let select = format!("select-window -t {}", quote(&target));
+            tmux_command()
+                .args(["set-hook", "-a", "-t", session_name])
+                .args(["client-session-changed", &select])
+                .status()?;
+        }
+        Ok(())
+    }
+
+    /// Attaches (or switches) to a new view of the project's running session,
+    /// optionally selecting a window (name or index) in it, and a pane of the
+    /// window.
+    ///
+    /// A view is a session grouped with the project's session, sharing its
+    /// windows but with its own current window, e.g. for a second monitor.
+    /// It's named `<session>-view-<number>`, destroyed once detached, and
+    /// stopped with the project.
+    pub fn attach_view(&self, window: Option<&str>, pane: Option<usize>) -> Result<(), TmuxError> {
+        self.ensure_running()?;
         let project_name = &self.project.project_name;
+        let window_index = match window {
+            Some(window) => {
+                let (window_index, pane_index) = self.resolve_selection(window, pane)?;
+                // The windows, and their current panes, are shared by the views
+                if let Some(pane_index) = pane_index {
+                    Commands::SelectPane {
+                        session_name: project_name,
+                        window_index,
+                        pane_index,
+                    }
+                    .run()?;
+                }
+                Some(window_index)
+            }
+            None => None,
+        };
+        let number = TmuxServer::get()
+            .session_names()
+            .iter()
+            .filter_map(|name| view_number(project_name, name))
+            .max()
+            .unwrap_or(0)
+            + 1;
+        let view_name = format!("{project_name}{VIEW_INFIX}{number}");
+        Commands::AttachView {
+            session_name: project_name,
+            view_name: &view_name,
+            window_index,
+            client: self.attach.client.as_deref(),
+        }
+        .run()
+    }
+
+    /// Helper resolving a window (name or index), and optionally a pane of
+    /// it, to tmux's `(window_index, pane_index)`, see
+    /// [`resolve_target`](Self::resolve_target).
+    fn resolve_selection(
+        &self,
+        window: &str,
+        pane: Option<usize>,
+    ) -> Result<(usize, Option<usize>), TmuxError> {
+        match pane {
+            Some(pane) => {
+                let (window_index, pane_index) =
+                    self.resolve_target(&format!("{window}.{pane}"))?;
+                Ok((window_index, Some(pane_index)))
+            }
+            None => Ok((self.resolve_target(window)?.0, None)),
+        }
+    }
+
+    /// Renames the project's session if it's running, returns whether it
+    /// was.
+    pub fn rename(&self, new_name: &str) -> Result<bool, TmuxError> {
+        if !self.session_exists()? {
+            return Ok(false);
+        }
+        Commands::RenameSession {
+            session_name: &self.project.project_name,
+            new_name,
+        }
+        .run()?;
+        Ok(true)
+    }
+
+    /// Sends keys to a pane of the running session, see
+    /// [`resolve_target`](Self::resolve_target) for the `target` format.
+    pub fn send_keys(
+        &self,
+        target: &str,
+        keys: &[String],
+        options: &SendOptions,
+    ) -> Result<(), TmuxError> {
+        self.ensure_running()?;
+        let (window_index, pane_index) = self.resolve_target(target)?;
+        let target = format!("{}:{window_index}.{pane_index}", self.project.project_name);
+        self.send_keys_to_pane(&target, keys, options)
+    }
+
+    /// Runs a command in all the panes of the running session, all the
+    /// panes of a single window (name or index), or a single pane of it, see
+    /// [`resolve_target`](Self::resolve_target).
+    pub fn exec(
+        &self,
+        window: Option<&str>,
+        command: &str,
+        options: &SendOptions,
+    ) -> Result<(), TmuxError> {
+        self.ensure_running()?;
+        let output = tmux_command()
+            .args(self.get_exec_panes_args(window)?)
+            .output()?;
+        if !output.status.success() {
+            return Err(TmuxError::Message(format!(
+                "Cannot list panes of {}",
+                self.project.project_name
+            )));
+        }
+
+        let keys = [command.to_string()];
+        for target in String::from_utf8_lossy(&output.stdout).lines() {
+            self.send_keys_to_pane(target, &keys, options)?;
+        }
+        Ok(())
+    }
+
+    /// Helper returning the arguments of the tmux command listing the panes
+    /// [`exec`](Self::exec) runs the command in, one target per line.
+    fn get_exec_panes_args(&self, window: Option<&str>) -> Result<Vec<String>, TmuxError> {
+        let session_name = &self.project.project_name;
+        let format = "#{session_name}:#{window_index}.#{pane_index}".to_string();
+        let args = match window {
+            Some(window) if self.find_window(window).is_some() => {
+                let (window_index, _) = self.resolve_target(window)?;
+                let target = format!("{session_name}:{window_index}");
+                vec![
+                    "list-panes".into(),
+                    "-t".into(),
+                    target,
+                    "-F".into(),
+                    format,
+                ]
+            }
+            Some(pane) => {
+                let (window_index, pane_index) = self.resolve_target(pane)?;
+                let target = format!("{session_name}:{window_index}.{pane_index}");
+                vec![
+                    "display-message".into(),
+                    "-p".into(),
+                    "-t".into(),
+                    target,
+                    format,
+                ]
+            }
+            None => vec![
+                "list-panes".into(),
+                "-s".into(),
+                "-t".into(),
+                session_name.clone(),
+                "-F".into(),
+                format,
+            ],
+        };
+        Ok(args)
+    }
+
+    /// Helper failing if the project's session isn't running.
+    fn ensure_running(&self) -> Result<(), TmuxError> {
+        if self.session_exists()? {
+            Ok(())
+        } else {
+            Err(TmuxError::Message(format!(
+                "Session {} is not running",
+                self.project.project_name
+            )))
+        }
+    }
+
+    /// Helper running `send-keys` for a `session:window.pane` target, and
+    /// waiting for the pane's output if requested.
+    fn send_keys_to_pane(
+        &self,
+        target: &str,
+        keys: &[String],
+        options: &SendOptions,
+    ) -> Result<(), TmuxError> {
+        let start_line = match options.wait_for_output {
+            Some(_) => {
+                // When pressing Enter, skip the line with the typed keys, to
+                // match the output only
+                let (history_size, cursor_y) = Self::pane_cursor(target)?;
+                Some(history_size + cursor_y + usize::from(options.enter))
+            }
+            None => None,
+        };
+
+        let mut args = vec!["send-keys", "-t", target];
+        if options.literal {
+            args.push("-l");
+        }
+        args.extend(keys.iter().map(String::as_str));
+        let mut cmds = vec![args];
+        if options.enter {
+            // With -l, "Enter" would be sent literally, hence a separate command
+            cmds.push(vec!["send-keys", "-t", target, "Enter"]);
+        }
+
+        for args in cmds {
+            let res = tmux_command().args(args).status()?;
+            if !res.success() {
+                return Err(TmuxError::Message(format!(
+                    "Cannot run send-keys for {target}"
+                )));
+            }
+        }
+
+        if let (Some(regex), Some(start_line)) = (&options.wait_for_output, start_line) {
+            Self::wait_for_output(target, regex, start_line, options.timeout)?;
+        }
+        Ok(())
+    }
+
+    /// Helper getting the pane's `(history_size, cursor_y)`. Their sum is
+    /// the absolute line of the cursor, including the pane's history.
+    fn pane_cursor(target: &str) -> Result<(usize, usize), TmuxError> {
+        let output = tmux_command()
+            .args([
+                "display-message",
+                "-p",
+                "-t",
+                target,
+                "#{history_size} #{cursor_y}",
+            ])
+            .output()?;
+        let values = String::from_utf8_lossy(&output.stdout)
+            .split_whitespace()
+            .map(|v| v.parse::<usize>())
+            .collect::<Result<Vec<_>, _>>()
+            .ok()
+            .filter(|v| v.len() == 2)
+            .ok_or_else(|| TmuxError::Message(format!("Cannot get cursor of pane {target}")))?;
+        Ok((values[0], values[1]))
+    }
+
+    /// Helper polling the pane's output written since `start_line` (the
+    /// absolute line, see [`pane_cursor`](Self::pane_cursor)) until it
+    /// matches `regex`.
+    fn wait_for_output(
+        target: &str,
+        regex: &Regex,
+        start_line: usize,
+        timeout: Duration,
+    ) -> Result<(), TmuxError> {
+        let started = Instant::now();
+        loop {
+            let (history_size, _) = Self::pane_cursor(target)?;
+            let start = start_line as i64 - history_size as i64;
+            let output = tmux_command()
+                .args(["capture-pane", "-p", "-J", "-t", target, "-S"])
+                .arg(start.to_string())
+                .output()?;
+            if regex.is_match(&String::from_utf8_lossy(&output.stdout)) {
+                return Ok(());
+            }
+            if started.elapsed() >= timeout {
+                return Err(TmuxError::Message(format!(
+                    "Timed out waiting for output matching {regex} in {target}"
+                )));
+            }
+            sleep(Duration::from_millis(200));
+        }
+    }
+
+    /// Prints the last `lines` of the pane's output (`capture-pane`), see
+    /// [`resolve_target`](Self::resolve_target) for the `target` format.
+    ///
+    /// With `follow`, keeps printing new output. If the pane's output is logged
+    /// (see [`LogSetting`](crate::project_config::log::LogSetting)) the log file is
+    /// followed, otherwise the pane is piped (replacing existing pipes) to a
+    /// temporary fifo.
+    pub fn logs(&self, target: &str, lines: usize, follow: bool) -> Result<(), TmuxError> {
+        self.ensure_running()?;
+        let (window_index, pane_index) = self.resolve_target(target)?;
+        let pane_target = format!("{}:{window_index}.{pane_index}", self.project.project_name);
 
-        let first_window_name = self
-            .project
-            .windows
-            .as_ref()
-            .and_then(|windows| windows.first())
-            .map(|w| w.name.as_str());
+        let output = tmux_command()
+            .args(["capture-pane", "-p", "-J", "-t", &pane_target, "-S"])
+            .arg(format!("-{lines}"))
+            .output()?;
+        if !output.status.success() {
+            return Err(TmuxError::Message(format!(
+                "Cannot capture pane {pane_target}"
+            )));
+        }
+        // Skip the empty lines at the bottom of the pane
+        let captured = String::from_utf8_lossy(&output.stdout);
+        println!("{}", captured.trim_end());
+
+        if !follow {
+            return Ok(());
+        }
+
+        let windows = self.project.windows.as_deref().unwrap_or_default();
+        let window = &windows[window_index - self.tmux.base_index];
+        match self.get_pane_log_file(&self.project.project_name, window, pane_index) {
+            Some(log_file) => Self::follow_file(&log_file),
+            None => self.follow_pane(&pane_target),
+        }
+    }
+
+    /// Helper returning the log file of a pane, if logging is enabled for
+    /// the window, see [`LogSetting`](crate::project_config::log::LogSetting).
+    ///
+    /// The file is `<log dir>/<session>/<window>.<pane>.log`.
+    fn get_pane_log_file(
+        &self,
+        session_name: &str,
+        w: &Window,
+        pane_index: usize,
+    ) -> Option<PathBuf> {
+        let log_dir = w.log.as_ref().or(self.project.log.as_ref())?.dir()?;
+        let window_name: String = w
+            .name
+            .chars()
+            .map(|c| match c {
+                '/' | '\\' | ' ' => '_',
+                _ => c,
+            })
+            .collect();
+
+        Some(
+            log_dir
+                .join(session_name)
+                .join(format!("{window_name}.{pane_index}.log")),
+        )
+    }
+
+    /// Helper printing the content appended to a file, until interrupted.
+    fn follow_file(log_file: &PathBuf) -> Result<(), TmuxError> {
+        let mut file = File::open(log_file)?;
+        file.seek(SeekFrom::End(0))?;
+        let mut buf = [0; 4096];
+        let mut stdout = io::stdout();
+        loop {
+            match file.read(&mut buf)? {
+                0 => sleep(Duration::from_millis(200)),
+                n => {
+                    stdout.write_all(&buf[..n])?;
+                    stdout.flush()?;
+                }
+            }
+        }
+    }
+
+    /// Helper piping the pane's output to a fifo and printing it, until the
+    /// pane is closed or interrupted.
+    ///
+    /// The fifo is created in a private temporary directory. Once interrupted
+    /// (e.g. Ctrl-C), the pipe is closed, ending the output, and both are
+    /// removed by [`PanePipe`].
+    fn follow_pane(&self, pane_target: &str) -> Result<(), TmuxError> {
+        let dir = tempfile::Builder::new()
+            .prefix(&format!("{}-", crate_name!()))
+            .permissions(Permissions::from_mode(0o700))
+            .tempdir()?;
+        let fifo = dir.path().join("pane.fifo");
+        let res = Command::new("mkfifo").arg(&fifo).status()?;
+        if !res.success() {
+            return Err(TmuxError::Message(format!(
+                "Cannot create fifo {}",
+                fifo.display()
+            )));
+        }
+
+        let pipe_command = format!("cat >> {}", shell_escape::escape(fifo.to_string_lossy()));
+        let res = tmux_command()
+            .args(["pipe-pane", "-t", pane_target, &pipe_command])
+            .status()?;
+        if !res.success() {
+            return Err(TmuxError::Message(format!(
+                "Cannot pipe pane {pane_target}"
+            )));
+        }
+        let _pipe = PanePipe {
+            target: pane_target.to_string(),
+            _dir: dir,
+        };
+        let target = pane_target.to_string();
+        ctrlc::set_handler(move || PanePipe::close(&target))
+            .map_err(|e| TmuxError::Message(format!("Cannot handle interrupts: {e}")))?;
+
+        // Blocks until `cat` opens the fifo for writing
+        let mut reader = File::open(&fifo)?;
+        let mut stdout = io::stdout();
+        let mut buf = [0; 4096];
+        loop {
+            match reader.read(&mut buf)? {
+                0 => break,
+                n => {
+                    stdout.write_all(&buf[..n])?;
+                    stdout.flush()?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the commands for creating the session as a JSON array of
+    /// structured steps, see [`PlanStep`](super::commands::PlanStep).
+    pub fn to_json(&self) -> Result<String, TmuxError> {
+        let commands = self.get_commands(StartMode::Fresh);
+        let steps: Vec<_> = commands
+            .iter()
+            .filter(|cmd| !cmd.is_noop())
+            .map(|cmd| cmd.plan_step())
+            .collect();
+        Ok(serde_json::to_string_pretty(&steps)?)
+    }
+
+    /// Returns the commands for creating the session as a tmux configuration
+    /// file, which can be used with `tmux source-file`.
+    pub fn to_tmux_conf(&self) -> String {
+        self.get_commands(StartMode::Fresh)
+            .iter()
+            .filter(|cmd| !cmd.is_noop())
+            .map(|cmd| cmd.tmux_conf())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    /// Helper returning the [`Commands`] for starting the project.
+    ///
+    /// Runs `on_project_first_start` or `on_project_restart` based on `mode`,
+    /// and skips the session creation commands when reattaching.
+    fn get_commands(&self, mode: StartMode) -> Vec<Commands<'_>> {
+        let project_name = &self.project.project_name;
 
         let mut commands = vec![
             Commands::Server {
@@ -133,19 +952,131 @@ impl<'a> TmuxProject<'a> {
                 project_root: &self.project.project_root,
             },
             Commands::ProjectEvent {
-                event_name: "start",
+                hook_name: "on_project_start",
                 on_event: &self.project.on_project_start,
             },
-            Commands::Session {
-                project_name,
-                first_window_name,
-            },
         ];
 
-        if let Some(project_root) = &self.project.project_root {
+        match mode {
+            StartMode::Fresh => {
+                commands.push(Commands::ProjectEvent {
+                    hook_name: "pre",
+                    on_event: &self.project.pre,
+                });
+                commands.push(Commands::ProjectEvent {
+                    hook_name: "on_project_first_start",
+                    on_event: &self.project.on_project_first_start,
+                });
+                commands.extend(self.get_sessions_commands());
+            }
+            StartMode::Reattach => commands.push(Commands::ProjectEvent {
+                hook_name: "on_project_restart",
+                on_event: &self.project.on_project_restart,
+            }),
+            StartMode::Restart(active) => {
+                commands.push(Commands::ProjectEvent {
+                    hook_name: "on_project_restart",
+                    on_event: &self.project.on_project_restart,
+                });
+                commands.extend(self.get_sessions_commands());
+                if let Some((window_index, pane_index)) = active {
+                    commands.push(Commands::SelectWindow {
+                        session_name: project_name,
+                        window_index,
+                    });
+                    commands.push(Commands::SelectPane {
+                        session_name: project_name,
+                        window_index,
+                        pane_index,
+                    });
+                }
+            }
+        }
+
+        if self.attach.attach {
+            commands.push(self.get_attach_session_command());
+        }
+
+        commands.push(Commands::ProjectEvent {
+            hook_name: "post",
+            on_event: &self.project.post,
+        });
+
+        commands.push(Commands::ProjectEvent {
+            hook_name: "on_project_exit",
+            on_event: &self.project.on_project_exit,
+        });
+
+        commands
+    }
+
+    /// Helper returning the project's own session.
+    fn main_session(&self) -> TmuxSession<'a> {
+        TmuxSession {
+            name: &self.project.project_name,
+            root: &self.project.project_root,
+            windows: self.project.windows.as_deref().unwrap_or_default(),
+        }
+    }
+
+    /// Helper returning the project's sessions, its own one first.
+    fn sessions(&self) -> Vec<TmuxSession<'a>> {
+        let project = self.project;
+        let additional = project
+            .sessions
+            .iter()
+            .flatten()
+            .map(|session| TmuxSession {
+                name: &session.name,
+                root: match session.root {
+                    Some(_) => &session.root,
+                    None => &project.project_root,
+                },
+                windows: &session.windows,
+            });
+        std::iter::once(self.main_session())
+            .chain(additional)
+            .collect()
+    }
+
+    /// Helper returning the [`Commands`] for creating all the project's
+    /// sessions, see [`get_session_commands`](Self::get_session_commands).
+    fn get_sessions_commands(&self) -> Vec<Commands<'_>> {
+        self.sessions()
+            .into_iter()
+            .flat_map(|session| self.get_session_commands(session))
+            .collect()
+    }
+
+    /// Helper returning the [`Commands`] for creating a session of the
+    /// project, its windows and panes.
+    fn get_session_commands(&'a self, session: TmuxSession<'a>) -> Vec<Commands<'a>> {
+        let session_name = session.name;
+        let first_window_name = session.windows.first().map(|w| w.name.as_str());
+
+        // Older versions can't set the environment of the new session, only
+        // for the windows created after it
+        let env = self.project.env.as_ref();
+        let env_on_create = self.tmux.version >= TmuxVersion::Version(3, 2);
+        let mut commands = vec![Commands::Session {
+            project_name: session_name,
+            first_window_name,
+            env: env.filter(|_| env_on_create),
+        }];
+        if !env_on_create {
+            commands.extend(env.iter().flat_map(|env| env.iter()).map(|(name, value)| {
+                Commands::SetEnvironment {
+                    session_name,
+                    name,
+                    value,
+                }
+            }));
+        }
+
+        if let Some(root) = session.root {
             commands.push(Commands::SendKeys {
-                command: format!("cd {}", &project_root),
-                session_name: project_name,
+                command: format!("cd {}", quote_path(root)),
+                session_name,
                 window_index: self.tmux.base_index,
                 pane_index: None,
                 comment: Some(
@@ -154,39 +1085,34 @@ impl<'a> TmuxProject<'a> {
             });
         }
 
-        if let Some(windows) = self.project.windows.as_ref() {
-            windows.iter().enumerate().for_each(|(idx, w)| {
-                commands.extend(self.get_window_commands(idx, w));
+        if !session.windows.is_empty() {
+            session.windows.iter().enumerate().for_each(|(idx, w)| {
+                commands.extend(self.get_window_commands(session, idx, w));
             });
 
             // select 1st window and 1st pane
             commands.push(Commands::SelectWindow {
-                session_name: project_name,
+                session_name,
                 window_index: self.tmux.base_index,
             });
 
-            commands.push(Commands::SelectPane {
-                session_name: project_name,
-                window_index: self.tmux.base_index,
-                pane_index: self.tmux.pane_base_index,
-            })
+            if session.windows[0].link.is_none() {
+                commands.push(Commands::SelectPane {
+                    session_name,
+                    window_index: self.tmux.base_index,
+                    pane_index: self.tmux.pane_base_index,
+                })
+            }
         }
 
         if self.tmux.version >= TmuxVersion::Version(2, 6) {
-            let hook_cmd = self.get_layout_hooks_command();
+            let hook_cmd = self.get_layout_hooks_command(session);
 
             if let Some(cmd) = hook_cmd {
                 commands.push(cmd);
             }
         }
 
-        commands.push(self.get_attach_session_command());
-
-        commands.push(Commands::ProjectEvent {
-            event_name: "exit",
-            on_event: &self.project.on_project_exit,
-        });
-
         commands
     }
 
@@ -195,21 +1121,131 @@ impl<'a> TmuxProject<'a> {
     fn get_attach_session_command(&self) -> Commands<'_> {
         Commands::AttachSession {
             session_name: &self.project.project_name,
+            read_only: self.attach.read_only,
+            detach_others: self.attach.detach_others,
+            client: self.attach.client.as_deref(),
+        }
+    }
+
+    /// Helper returning the name of the `tmux wait-for` channel signaled
+    /// once the commands of a waited for pane exit.
+    fn wait_channel(&self, window_index: usize, pane_index: usize) -> String {
+        format!(
+            "{}-{}-{window_index}-{pane_index}",
+            crate_name!(),
+            self.project.project_name
+        )
+    }
+
+    /// Helper blocking until the commands of the waited for panes exit.
+    ///
+    /// Returns the error for the first pane which exited with a non-zero
+    /// status.
+    fn wait_for_panes(&self) -> Result<(), TmuxError> {
+        let mut failed = None;
+        for (window_index, pane_index) in self.wait.iter() {
+            let channel = self.wait_channel(*window_index, *pane_index);
+            tmux_command().args(["wait-for", &channel]).status()?;
+
+            let target = format!("{}:{window_index}.{pane_index}", self.project.project_name);
+            let output = tmux_command()
+                .args([
+                    "display-message",
+                    "-p",
+                    "-t",
+                    &target,
+                    &format!("#{{{EXIT_STATUS_OPTION}}}"),
+                ])
+                .output()?;
+            let code = String::from_utf8_lossy(&output.stdout)
+                .trim()
+                .parse::<i32>()
+                .map_err(|_| {
+                    TmuxError::Message(format!("Cannot get the exit status of pane {target}"))
+                })?;
+            if code != 0 && failed.is_none() {
+                failed = Some(TmuxError::PaneExit { target, code });
+            }
         }
+
+        failed.map_or(Ok(()), Err)
     }
 
     /// Helper returning the [`Commands`] for stopping to an
-    /// already running session.
-    fn get_stop_session_commands(&self) -> Vec<Commands<'_>> {
-        vec![
-            Commands::StopSession {
-                session_name: &self.project.project_name,
-            },
-            Commands::ProjectEvent {
-                event_name: "stop",
-                on_event: &self.project.on_project_stop,
-            },
-        ]
+    /// already running session, its views, and the project's additional
+    /// sessions which are `running`.
+    fn get_stop_session_commands(&'a self, running: &'a [String]) -> Vec<Commands<'a>> {
+        let project_name = &self.project.project_name;
+        let mut commands = vec![Commands::ProjectEvent {
+            hook_name: "on_project_stop",
+            on_event: &self.project.on_project_stop,
+        }];
+        commands.extend(
+            view_names(project_name, running)
+                .into_iter()
+                .map(|session_name| Commands::StopSession { session_name }),
+        );
+        commands.extend(
+            self.sessions()
+                .into_iter()
+                .filter(|session| {
+                    session.name == project_name || running.iter().any(|name| name == session.name)
+                })
+                .map(|session| Commands::StopSession {
+                    session_name: session.name,
+                }),
+        );
+        commands
+    }
+
+    /// Helper running the [`get_stop_session_commands`](Self::get_stop_session_commands).
+    fn run_stop_session_commands(&self) -> Result<(), TmuxError> {
+        let running = TmuxServer::get().session_names();
+        for cmd in self.get_stop_session_commands(&running) {
+            cmd.run()?;
+        }
+        Ok(())
+    }
+
+    /// Helper returning the [`Commands`] re-creating a single window in
+    /// place. `new-window -k` kills the existing window at the same index.
+    fn get_restart_window_commands(
+        &'a self,
+        window_name: &str,
+    ) -> Result<Vec<Commands<'a>>, TmuxError> {
+        let windows = self.project.windows.as_deref().unwrap_or_default();
+        let (idx, window) = windows
+            .iter()
+            .enumerate()
+            .find(|(_, w)| w.name == window_name)
+            .ok_or_else(|| TmuxError::Message(format!("Unknown window {window_name}")))?;
+
+        Ok(self.get_window_commands(self.main_session(), idx, window))
+    }
+
+    /// Helper getting the `(window_index, pane_index)` of the session's active
+    /// window and pane, resolved by the window's name against the project
+    /// file. `None` if the window isn't in the project file.
+    fn get_active_target(&self) -> Result<Option<(usize, usize)>, TmuxError> {
+        let output = tmux_command()
+            .args([
+                "display-message",
+                "-p",
+                "-t",
+                &self.project.project_name,
+                "#{pane_index}:#{window_name}",
+            ])
+            .output()?;
+        let active = String::from_utf8_lossy(&output.stdout);
+        let Some((pane, window_name)) = active.trim_end_matches('\n').split_once(':') else {
+            return Ok(None);
+        };
+
+        let target = format!("{window_name}.{pane}");
+        Ok(self
+            .resolve_target(&target)
+            .or_else(|_| self.resolve_target(window_name))
+            .ok())
     }
 
     /// Helper returning the commands for creating the [`Window`]s
@@ -218,12 +1254,29 @@ impl<'a> TmuxProject<'a> {
     ///
     /// The index of the window, `idx`, is `0` based, and is adjusted for
     /// the current tmux configuration for `base-index` and `pane-base-index`.
-    fn get_window_commands(&'a self, idx: usize, w: &'a Window) -> Vec<Commands<'a>> {
+    ///
+    /// A linked window is linked in place of the window at the index instead.
+    fn get_window_commands(
+        &'a self,
+        session: TmuxSession<'a>,
+        idx: usize,
+        w: &'a Window,
+    ) -> Vec<Commands<'a>> {
         let mut commands = Vec::new();
-        let project_name = &self.project.project_name;
-        let window_root = w.root.clone().or_else(|| self.project.project_root.clone());
+        let project_name = session.name;
+        let window_root = w.root.clone().or_else(|| session.root.clone());
 
         let window_idx = idx + self.tmux.base_index;
+        if let Some(source) = &w.link {
+            commands.push(Commands::LinkWindow {
+                session_name: project_name,
+                window_index: window_idx,
+                source,
+            });
+            return commands;
+        }
+        // The panes to wait for are of the project's own session
+        let is_main_session = session.name == self.project.project_name;
         commands.push(Commands::NewWindow {
             session_name: project_name,
             window_name: w.name.as_ref(),
@@ -251,6 +1304,14 @@ impl<'a> TmuxProject<'a> {
                     window_root: window_root.clone(),
                 })
             }
+            if let Some(log_file) = self.get_pane_log_file(project_name, w, pane_with_base_idx) {
+                commands.push(Commands::PipePane {
+                    session_name: project_name,
+                    window_index: window_idx,
+                    pane_index: pane_with_base_idx,
+                    log_file,
+                });
+            }
             if let Some(pre_window) = &self.project.pre_window {
                 pre_window.iter().enumerate().for_each(|(cmd_idx, cmd)| {
                     let comment = if idx == 0 && pane_idx == 0 && cmd_idx == 0 {
@@ -278,12 +1339,25 @@ impl<'a> TmuxProject<'a> {
                     });
                 }
             }
+            if is_main_session && self.wait.contains(&(window_idx, pane_with_base_idx)) {
+                commands.push(Commands::SendKeys {
+                    command: format!(
+                        "{tmux} set-option -p -t \"$TMUX_PANE\" {EXIT_STATUS_OPTION} $?; {tmux} wait-for -S {}",
+                        quote(&self.wait_channel(window_idx, pane_with_base_idx)),
+                        tmux = tmux_prefix(),
+                    ),
+                    session_name: project_name,
+                    window_index: window_idx,
+                    pane_index: Some(pane_with_base_idx),
+                    comment: None,
+                });
+            }
 
             // apply layout again, to have panes correctly sized
             commands.push(Commands::SelectLayout {
                 session_name: project_name,
                 window_index: window_idx,
-                layout: &w.layout,
+                layout: w.layout(),
             });
         });
 
@@ -291,7 +1365,7 @@ impl<'a> TmuxProject<'a> {
             commands.push(Commands::SelectLayout {
                 session_name: project_name,
                 window_index: window_idx,
-                layout: &w.layout,
+                layout: w.layout(),
             });
 
             commands.push(Commands::SelectPane {
@@ -303,63 +1377,757 @@ impl<'a> TmuxProject<'a> {
         commands
     }
 
-    /// Helper checking if the project's session is already running by
-    /// utilizing `tmux has-session`.
+    /// Helper checking if the project's session is already running, see
+    /// [`has_session`].
     fn session_exists(&self) -> Result<bool, TmuxError> {
-        let res = Command::new(TMUX_BIN)
-            .env_remove("TMUX")
-            .arg("has-session")
-            .arg("-t")
-            .arg(&self.project.project_name)
-            .output()?;
-
-        Ok(res.status.success())
+        has_session(&self.project.project_name)
     }
 
     /// Get to hook commands to correctly apply layouts
-    fn get_layout_hooks_command(&self) -> Option<Commands<'_>> {
+    fn get_layout_hooks_command(&self, session: TmuxSession<'a>) -> Option<Commands<'a>> {
         // No windows? No need to set layouts
-        self.project.windows.as_ref()?;
-
-        let windows = self.project.windows.as_ref().unwrap();
-        if windows.is_empty() {
+        if session.windows.is_empty() {
             return None;
         }
 
+        // The hook's body is parsed by tmux, so the values are quoted
         let mut hook_commands: Vec<String> = Vec::new();
-        windows.iter().enumerate().for_each(|(idx, w)| {
-            // Need to select window before applying layout
-            hook_commands.push(format!("selectw -t {}", self.tmux.base_index + idx));
-            hook_commands.push(format!("selectl {}", w.layout));
-            if idx > 0 {
-                hook_commands.push("selectw -l".into());
-            }
-        });
+        // Linked windows keep the layout of their session
+        let windows = session.windows.iter().enumerate();
+        windows
+            .filter(|(_, w)| w.link.is_none())
+            .for_each(|(idx, w)| {
+                // Need to select window before applying layout
+                let target = format!("{}:{}", session.name, self.tmux.base_index + idx);
+                hook_commands.push(format!("selectw -t {}", quote(&target)));
+                hook_commands.push(format!("selectl {}", quote(w.layout())));
+                if idx > 0 {
+                    hook_commands.push("selectw -l".into());
+                }
+            });
 
         // Once done, unset the hook
         hook_commands.push(format!(
             "set-hook -u -t {} client-session-changed",
-            self.project.project_name
+            quote(session.name)
         ));
 
-        let hook_command = hook_commands.join(";");
+        let hook_command = hook_commands.join(" ; ");
 
         Some(Commands::SetHook {
-            session_name: &self.project.project_name,
+            session_name: session.name,
             hook_name: "client-session-changed",
             hook_command,
         })
     }
 }
 
+/// Returns the names of the views of the session among the session names,
+/// see [`TmuxProject::attach_view`].
+pub fn view_names<'s>(session_name: &str, session_names: &'s [String]) -> Vec<&'s str> {
+    session_names
+        .iter()
+        .filter(|name| view_number(session_name, name).is_some())
+        .map(String::as_str)
+        .collect()
+}
+
+/// Returns the number of the view, if the name is of a view of the session,
+/// `<session>-view-<number>`.
+fn view_number(session_name: &str, name: &str) -> Option<usize> {
+    name.strip_prefix(session_name)?
+        .strip_prefix(VIEW_INFIX)?
+        .parse()
+        .ok()
+}
+
+/// A pane piped to a fifo by [`TmuxProject::logs`], closing the pipe and
+/// removing the fifo's directory when dropped.
+struct PanePipe {
+    /// The piped pane.
+    target: String,
+    /// The private directory of the fifo, removed when dropped.
+    _dir: tempfile::TempDir,
+}
+
+impl PanePipe {
+    /// Closes the pane's pipe, ending the output read from the fifo.
+    fn close(target: &str) {
+        let _ = tmux_command().args(["pipe-pane", "-t", target]).status();
+    }
+}
+
+impl Drop for PanePipe {
+    fn drop(&mut self) {
+        Self::close(&self.target);
+    }
+}
+
+/// Returns the tmux commands moving the client to the holding session, then
+/// running the command in the background from the directory, so that it
+/// survives the sessions it kills.
+fn restart_in_background_args(
+    holding: &str,
+    client: &str,
+    dir: &str,
+    command: &[String],
+) -> Vec<String> {
+    let command: Vec<_> = command.iter().map(|arg| quote(arg)).collect();
+    // tmux expands formats in run-shell's command
+    let shell_command =
+        format!("cd {} && exec {}", quote(dir), command.join(" ")).replace('#', "##");
+    [
+        "new-session",
+        "-d",
+        "-s",
+        holding,
+        ";",
+        "switch-client",
+        "-c",
+        client,
+        "-t",
+        holding,
+        ";",
+        "run-shell",
+        "-b",
+        &shell_command,
+    ]
+    .map(String::from)
+    .to_vec()
+}
+
+/// Checks if a session is running by utilizing `tmux has-session`.
+fn has_session(session_name: &str) -> Result<bool, TmuxError> {
+    let res = tmux_command()
+        .env_remove("TMUX")
+        .arg("has-session")
+        .arg("-t")
+        .arg(session_name)
+        .output()?;
+
+    Ok(res.status.success())
+}
+
 impl<'a> fmt::Display for TmuxProject<'a> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let joined = self
-            .get_commands()
+            .get_commands(StartMode::Fresh)
             .iter()
+            .filter(|cmd| !cmd.is_noop())
             .map(|x| format!("{x}"))
             .collect::<Vec<String>>()
             .join("\n");
         write!(f, "{joined}")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOOKS_PROJECT: &str = "\
+project_name: hooks
+on_project_start: echo start
+on_project_first_start: echo first_start
+on_project_restart: echo restart
+on_project_exit: echo exit
+on_project_stop: echo stop
+pre: echo pre
+post: echo post
+windows:
+  - editor: vim";
+
+    fn project_from(yaml: &str) -> ProjectConfig {
+        ProjectConfig::try_from(yaml.to_string()).unwrap()
+    }
+
+    fn tmux_project(project: &ProjectConfig) -> TmuxProject<'_> {
+        TmuxProject {
+            tmux: Tmux::new(0, 0, TmuxVersion::Version(3, 3)),
+            project,
+            attach: AttachOptions::default(),
+            wait: vec![],
+        }
+    }
+
+    /// Names of the hooks, in order, with the commands attached to them.
+    fn hooks<'a>(commands: &'a [Commands<'a>]) -> Vec<(&'a str, Vec<String>)> {
+        commands
+            .iter()
+            .filter_map(|cmd| match cmd {
+                Commands::ProjectEvent {
+                    hook_name,
+                    on_event: Some(on_event),
+                } => Some((*hook_name, on_event.clone())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn hook_position(commands: &[Commands], name: &str) -> usize {
+        commands
+            .iter()
+            .position(
+                |c| matches!(c, Commands::ProjectEvent { hook_name, .. } if *hook_name == name),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn fresh_start_hooks() {
+        let project = project_from(HOOKS_PROJECT);
+        let tmux = tmux_project(&project);
+        let commands = tmux.get_commands(StartMode::Fresh);
+
+        assert_eq!(
+            hooks(&commands),
+            vec![
+                ("on_project_start", vec!["echo start".into()]),
+                ("pre", vec!["echo pre".into()]),
+                ("on_project_first_start", vec!["echo first_start".into()]),
+                ("post", vec!["echo post".into()]),
+                ("on_project_exit", vec!["echo exit".into()]),
+            ]
+        );
+
+        let first_start_idx = hook_position(&commands, "on_project_first_start");
+        let session_idx = commands
+            .iter()
+            .position(|c| matches!(c, Commands::Session { .. }))
+            .unwrap();
+        let attach_idx = commands
+            .iter()
+            .position(|c| matches!(c, Commands::AttachSession { .. }))
+            .unwrap();
+        let post_idx = hook_position(&commands, "post");
+        assert!(first_start_idx < session_idx);
+        assert!(session_idx < attach_idx);
+        assert!(attach_idx < post_idx);
+    }
+
+    #[test]
+    fn reattach_hooks() {
+        let project = project_from(HOOKS_PROJECT);
+        let tmux = tmux_project(&project);
+        let commands = tmux.get_commands(StartMode::Reattach);
+
+        assert_eq!(
+            hooks(&commands),
+            vec![
+                ("on_project_start", vec!["echo start".into()]),
+                ("on_project_restart", vec!["echo restart".into()]),
+                ("post", vec!["echo post".into()]),
+                ("on_project_exit", vec!["echo exit".into()]),
+            ]
+        );
+        assert!(!commands
+            .iter()
+            .any(|c| matches!(c, Commands::Session { .. } | Commands::NewWindow { .. })));
+        assert!(commands
+            .iter()
+            .any(|c| matches!(c, Commands::AttachSession { .. })));
+        assert!(!commands.iter().any(|c| matches!(
+            c,
+            Commands::ProjectEvent {
+                hook_name: "pre",
+                ..
+            }
+        )));
+    }
+
+    #[test]
+    fn stop_hooks() {
+        let project = project_from(HOOKS_PROJECT);
+        let tmux = tmux_project(&project);
+        let commands = tmux.get_stop_session_commands(&[]);
+
+        assert_eq!(
+            hooks(&commands),
+            vec![("on_project_stop", vec!["echo stop".into()])]
+        );
+        assert!(matches!(commands[0], Commands::ProjectEvent { .. }));
+        assert!(matches!(commands[1], Commands::StopSession { .. }));
+    }
+
+    #[test]
+    fn no_attach() {
+        let project = project_from("project_name: detached\nattach: false");
+        let mut tmux = tmux_project(&project);
+        tmux.attach.attach = project.attach.unwrap();
+        let commands = tmux.get_commands(StartMode::Fresh);
+        assert!(!commands
+            .iter()
+            .any(|c| matches!(c, Commands::AttachSession { .. })));
+    }
+
+    #[test]
+    fn resolve_targets() {
+        let project = project_from(
+            "\
+project_name: targets
+windows:
+  - editor: vim
+  - server.log:
+      panes:
+        - tail -f server.log
+        - #",
+        );
+        let mut tmux = tmux_project(&project);
+        assert_eq!(tmux.resolve_target("editor").unwrap(), (0, 0));
+        assert_eq!(tmux.resolve_target("server.log").unwrap(), (1, 0));
+        assert_eq!(tmux.resolve_target("server.log.1").unwrap(), (1, 1));
+        assert_eq!(tmux.resolve_target("1.1").unwrap(), (1, 1));
+        assert!(tmux.resolve_target("server.log.2").is_err());
+        assert!(tmux.resolve_target("missing").is_err());
+
+        tmux.tmux = Tmux::new(1, 1, TmuxVersion::Version(3, 3));
+        assert_eq!(tmux.resolve_target("editor").unwrap(), (1, 1));
+        assert_eq!(tmux.resolve_target("server.log.2").unwrap(), (2, 2));
+        assert_eq!(tmux.resolve_target("2").unwrap(), (2, 1));
+        assert!(tmux.resolve_target("0").is_err());
+    }
+
+    #[test]
+    fn exec_panes() {
+        let project = project_from(
+            "\
+project_name: app
+windows:
+  - editor: vim
+  - server:
+      panes:
+        - npm start
+        - #",
+        );
+        let tmux = tmux_project(&project);
+        let format = "#{session_name}:#{window_index}.#{pane_index}";
+        assert_eq!(
+            tmux.get_exec_panes_args(None).unwrap(),
+            ["list-panes", "-s", "-t", "app", "-F", format]
+        );
+        assert_eq!(
+            tmux.get_exec_panes_args(Some("server")).unwrap(),
+            ["list-panes", "-t", "app:1", "-F", format]
+        );
+        assert_eq!(
+            tmux.get_exec_panes_args(Some("server.1")).unwrap(),
+            ["display-message", "-p", "-t", "app:1.1", format]
+        );
+        assert!(tmux.get_exec_panes_args(Some("server.2")).is_err());
+        assert!(tmux.get_exec_panes_args(Some("missing")).is_err());
+    }
+
+    /// Returns the command signaling the end of the `make` pane.
+    fn wait_signal(project: &ProjectConfig) -> String {
+        let tmux = tmux_project(project).with_wait(&["build".into()]).unwrap();
+        let commands = tmux.get_commands(StartMode::Fresh);
+        commands
+            .into_iter()
+            .filter_map(|c| match c {
+                Commands::SendKeys { command, .. } => Some(command),
+                _ => None,
+            })
+            .skip_while(|command| *command != "make")
+            .nth(1)
+            .unwrap()
+    }
+
+    #[test]
+    fn wait_signals_pane() {
+        let project = project_from("project_name: waiting\nwindows:\n  - build: make");
+        assert!(wait_signal(&project).ends_with("wait-for -S rusmux-waiting-0-0"));
+
+        // The channel is a single word for the pane's shell
+        let project = project_from("project_name: my app;$x\nwindows:\n  - build: make");
+        let signal = shlex::split(&wait_signal(&project)).unwrap();
+        assert_eq!(
+            signal[signal.len() - 3..],
+            ["wait-for", "-S", "rusmux-my app;$x-0-0"]
+        );
+    }
+
+    #[test]
+    fn restart_hooks() {
+        let project = project_from(HOOKS_PROJECT);
+        let tmux = tmux_project(&project);
+        let commands = tmux.get_commands(StartMode::Restart(Some((0, 0))));
+
+        assert_eq!(
+            hooks(&commands),
+            vec![
+                ("on_project_start", vec!["echo start".into()]),
+                ("on_project_restart", vec!["echo restart".into()]),
+                ("post", vec!["echo post".into()]),
+                ("on_project_exit", vec!["echo exit".into()]),
+            ]
+        );
+        assert!(
+            hook_position(&commands, "on_project_restart")
+                < commands
+                    .iter()
+                    .position(|c| matches!(c, Commands::Session { .. }))
+                    .unwrap()
+        );
+    }
+
+    #[test]
+    fn restart_selects_active_window() {
+        let project = project_from(
+            "project_name: active\nwindows:\n  - editor: vim\n  - shells:\n      panes:\n        - #\n        - #",
+        );
+        let tmux = tmux_project(&project);
+        let commands = tmux.get_commands(StartMode::Restart(Some((1, 1))));
+        let attach_idx = commands
+            .iter()
+            .position(|c| matches!(c, Commands::AttachSession { .. }))
+            .unwrap();
+
+        assert!(matches!(
+            commands[attach_idx - 2],
+            Commands::SelectWindow {
+                window_index: 1,
+                ..
+            }
+        ));
+        assert!(matches!(
+            commands[attach_idx - 1],
+            Commands::SelectPane {
+                window_index: 1,
+                pane_index: 1,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn restart_window() {
+        let project = project_from(
+            "project_name: windows\nwindows:\n  - editor: vim\n  - server: npm run serve",
+        );
+        let tmux = tmux_project(&project);
+        let commands = tmux.get_restart_window_commands("server").unwrap();
+
+        assert!(matches!(
+            commands[0],
+            Commands::NewWindow {
+                window_name: "server",
+                window_index: 1,
+                ..
+            }
+        ));
+        assert!(commands.iter().all(|c| !matches!(
+            c,
+            Commands::NewWindow {
+                window_name: "editor",
+                ..
+            }
+        )));
+        assert!(tmux.get_restart_window_commands("missing").is_err());
+    }
+
+    #[test]
+    fn restart_from_own_sessions() {
+        let project = project_from(
+            "\
+project_name: app
+windows:
+  - server: npm start
+sessions:
+  - name: app-ops
+    windows:
+      - deploy:
+",
+        );
+        let tmux = tmux_project(&project);
+        assert!(tmux.is_own_session("app"));
+        assert!(tmux.is_own_session("app-ops"));
+        assert!(tmux.is_own_session("app-view-2"));
+        assert!(!tmux.is_own_session("app-restarting"));
+        assert!(!tmux.is_own_session("other"));
+        assert_eq!(tmux.holding_session_name(), "app-restarting");
+    }
+
+    #[test]
+    fn restart_in_background() {
+        let command = ["/usr/bin/rusmux", "restart", "my app;x", "#{x}"].map(String::from);
+        let args = restart_in_background_args(
+            "my app;x-restarting",
+            "/dev/pts/1",
+            "/home/me/src",
+            &command,
+        );
+        assert_eq!(
+            args,
+            [
+                "new-session",
+                "-d",
+                "-s",
+                "my app;x-restarting",
+                ";",
+                "switch-client",
+                "-c",
+                "/dev/pts/1",
+                "-t",
+                "my app;x-restarting",
+                ";",
+                "run-shell",
+                "-b",
+                "cd /home/me/src && exec /usr/bin/rusmux restart 'my app;x' '##{x}'",
+            ]
+        );
+    }
+
+    #[test]
+    fn sessions_and_links() {
+        let project = project_from(
+            "\
+project_name: app
+project_root: ~/src/app
+windows:
+  - server: npm start
+  - logs: tail -f log/development.log
+sessions:
+  - name: app-ops
+    root: ~/src/app/ops
+    windows:
+      - deploy:
+      - logs:
+          link: app:logs",
+        );
+        let tmux = tmux_project(&project);
+        let commands = tmux.get_commands(StartMode::Fresh);
+        let sessions: Vec<_> = commands
+            .iter()
+            .filter_map(|c| match c {
+                Commands::Session { project_name, .. } => Some(*project_name),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(sessions, vec!["app", "app-ops"]);
+        assert!(commands.iter().any(|c| matches!(
+            c,
+            Commands::NewWindow {
+                session_name: "app-ops",
+                window_name: "deploy",
+                window_root: Some(root),
+                ..
+            } if root == "~/src/app/ops"
+        )));
+        assert!(commands.iter().any(|c| matches!(
+            c,
+            Commands::LinkWindow {
+                session_name: "app-ops",
+                window_index: 1,
+                source: "app:logs",
+            }
+        )));
+        assert!(!commands.iter().any(|c| matches!(
+            c,
+            Commands::NewWindow {
+                session_name: "app-ops",
+                window_name: "logs",
+                ..
+            }
+        )));
+        // The project's own session is attached to
+        assert!(matches!(
+            commands
+                .iter()
+                .find(|c| matches!(c, Commands::AttachSession { .. })),
+            Some(Commands::AttachSession {
+                session_name: "app",
+                ..
+            })
+        ));
+
+        let running = ["app", "app-view-1", "app-ops", "app-view-x"].map(String::from);
+        let stopped: Vec<_> = tmux
+            .get_stop_session_commands(&running)
+            .into_iter()
+            .filter_map(|c| match c {
+                Commands::StopSession { session_name } => Some(session_name),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(stopped, vec!["app-view-1", "app", "app-ops"]);
+    }
+
+    #[test]
+    fn log_panes() {
+        let project = project_from(
+            "\
+project_name: logs
+log: /tmp/logs
+windows:
+  - server: npm run serve
+  - shells:
+      log: false
+      panes:
+        - #
+  - dev server:
+      log: /var/log
+      panes:
+        - #
+        - #",
+        );
+        let tmux = tmux_project(&project);
+        let log_files: Vec<_> = tmux
+            .get_commands(StartMode::Fresh)
+            .into_iter()
+            .filter_map(|c| match c {
+                Commands::PipePane {
+                    window_index,
+                    pane_index,
+                    log_file,
+                    ..
+                } => Some((window_index, pane_index, log_file)),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(
+            log_files,
+            vec![
+                (0, 0, PathBuf::from("/tmp/logs/logs/server.0.log")),
+                (2, 0, PathBuf::from("/var/log/logs/dev_server.0.log")),
+                (2, 1, PathBuf::from("/var/log/logs/dev_server.1.log")),
+            ]
+        );
+    }
+
+    /// Fixtures for the snapshot tests, based on the ones in
+    /// `project_config`'s tests.
+    const SNAPSHOT_FIXTURES: [(&str, &str); 2] = [
+        (
+            "with_windows",
+            "\
+project_name: with-windows
+project_root: /home/dummy/void
+windows:
+  - editor: vim",
+        ),
+        (
+            "demo",
+            "\
+project_name: demo
+project_root: ~/src/demo
+on_project_start:
+  - sudo systemctl start postgresql
+pre_window:
+  - workon demo
+windows:
+  - editor: vim
+  - window4:
+      layout: main-vertical
+      root: /home/dummy/void
+      options:
+        main-pane-width: 75%
+      panes:
+        - vim
+        - #
+        - npm run serve
+  - greek-window:
+      panes:
+        - alpha-pane:
+          - echo alpha1
+          - echo alpha2
+        - echo delta",
+        ),
+    ];
+
+    #[test]
+    fn parse_tmux_config() {
+        let tmux = Tmux::parse_config("base-index 1\npane-base-index 2\n3.3a\n").unwrap();
+        assert_eq!(
+            (tmux.base_index, tmux.pane_base_index, tmux.version),
+            (1, 2, TmuxVersion::Version(3, 3))
+        );
+
+        let tmux = Tmux::parse_config("base-index 0\npane-base-index 0\n").unwrap();
+        assert_eq!(tmux.version, TmuxVersion::Max);
+
+        assert!(Tmux::parse_config("").is_none());
+        assert!(Tmux::parse_config("base-index one\npane-base-index 0\n").is_none());
+        assert!(Tmux::parse_config("pane-base-index 0\nbase-index 0\n").is_none());
+    }
+
+    #[test]
+    fn complete_settings() {
+        let project = project_from(HOOKS_PROJECT);
+        let settings = TmuxSettings {
+            base_index: Some(1),
+            pane_base_index: Some(1),
+            version: Some(TmuxVersion::Version(2, 9)),
+        };
+        let tmux = TmuxProject::with_settings(&project, &settings);
+        assert_eq!(
+            (
+                tmux.tmux.base_index,
+                tmux.tmux.pane_base_index,
+                tmux.tmux.version
+            ),
+            (1, 1, TmuxVersion::Version(2, 9))
+        );
+    }
+
+    #[test]
+    fn layout_hook_quoting() {
+        let project = project_from(
+            "project_name: my proj;x\nwindows:\n  - my logs:\n      layout: main-vertical\n",
+        );
+        let tmux = tmux_project(&project);
+        let Some(Commands::SetHook { hook_command, .. }) =
+            tmux.get_layout_hooks_command(tmux.main_session())
+        else {
+            panic!("expected the layout hook");
+        };
+        // tmux parses the hook's body like a shell would
+        let tokens = shlex::split(&hook_command).unwrap();
+        let commands: Vec<_> = tokens.split(|token| token == ";").collect();
+        assert_eq!(
+            commands,
+            vec![
+                &["selectw", "-t", "my proj;x:0"][..],
+                &["selectl", "main-vertical"],
+                &[
+                    "set-hook",
+                    "-u",
+                    "-t",
+                    "my proj;x",
+                    "client-session-changed"
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn snapshot_bash() {
+        for (name, yaml) in SNAPSHOT_FIXTURES {
+            let project = project_from(yaml);
+            let tmux = tmux_project(&project);
+            // The shebang depends on $SHELL, and the expanded paths on $HOME
+            let home = regex::escape(&shellexpand::tilde("~"));
+            insta::with_settings!({filters => vec![(r"^#!.*", ""), (home.as_str(), "~")]}, {
+                insta::assert_snapshot!(format!("bash_{name}"), tmux.to_string());
+            });
+        }
+    }
+
+    #[test]
+    fn snapshot_json() {
+        for (name, yaml) in SNAPSHOT_FIXTURES {
+            let project = project_from(yaml);
+            let tmux = tmux_project(&project);
+            insta::assert_snapshot!(format!("json_{name}"), tmux.to_json().unwrap());
+        }
+    }
+
+    #[test]
+    fn snapshot_tmux_conf() {
+        for (name, yaml) in SNAPSHOT_FIXTURES {
+            let project = project_from(yaml);
+            let tmux = tmux_project(&project);
+            insta::assert_snapshot!(format!("tmux_conf_{name}"), tmux.to_tmux_conf());
+        }
+    }
+}
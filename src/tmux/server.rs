@@ -0,0 +1,145 @@
+//! The tmux binary and server socket used by the tmux commands.
+use super::commands::{join_args, quote};
+use super::TmuxError;
+use serde::Deserialize;
+use std::process::Command;
+use std::sync::OnceLock;
+use std::{env, io};
+
+/// The default tmux binary, looked up in `$PATH`.
+pub const TMUX_BIN: &str = "tmux";
+
+/// The server set with [`TmuxServer::set`].
+static SERVER: OnceLock<TmuxServer> = OnceLock::new();
+
+/// The tmux binary, and the socket of the server. For example, in the global
+/// settings file:
+///
+/// ```yaml
+/// tmux:
+///   binary: /opt/tmux/bin/tmux
+///   socket: work
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct TmuxServer {
+    /// The tmux binary (optional), defaults to [`TMUX_BIN`].
+    pub binary: Option<String>,
+    /// The socket name of the server (optional), tmux's `-L` option.
+    pub socket: Option<String>,
+}
+
+impl TmuxServer {
+    /// Sets the server used by all the tmux commands.
+    pub fn set(server: TmuxServer) {
+        let _ = SERVER.set(server);
+    }
+
+    /// Returns the server set with [`TmuxServer::set`], or the default one.
+    pub fn get() -> &'static TmuxServer {
+        SERVER.get_or_init(TmuxServer::default)
+    }
+
+    /// Returns the tmux binary.
+    pub fn binary(&self) -> &str {
+        self.binary.as_deref().unwrap_or(TMUX_BIN)
+    }
+
+    /// Returns the names of the sessions running on the server, none if the
+    /// server isn't running.
+    pub fn session_names(&self) -> Vec<String> {
+        let output = tmux_command()
+            .args(["list-sessions", "-F", "#{session_name}"])
+            .output();
+        match output {
+            Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .map(String::from)
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Returns the name of the current client's session, when running inside
+    /// tmux.
+    pub fn current_session(&self) -> Option<String> {
+        env::var_os("TMUX")?;
+        let output = tmux_command()
+            .args(["display-message", "-p", "#{session_name}"])
+            .output()
+            .ok()?;
+        output.status.success().then(|| {
+            String::from_utf8_lossy(&output.stdout)
+                .trim_end()
+                .to_string()
+        })
+    }
+
+    /// Returns the name of the current client, inside tmux.
+    pub fn current_client(&self) -> Option<String> {
+        env::var_os("TMUX")?;
+        let output = tmux_command()
+            .args(["display-message", "-p", "#{client_name}"])
+            .output()
+            .ok()?;
+        let client = String::from_utf8_lossy(&output.stdout)
+            .trim_end()
+            .to_string();
+        (output.status.success() && !client.is_empty()).then_some(client)
+    }
+
+    /// Runs the current command again in a popup of the current client,
+    /// closed once it exits (tmux 3.2 or later), e.g. to give a terminal to a
+    /// prompt run from `run-shell`.
+    pub fn rerun_in_popup(&self) -> Result<(), TmuxError> {
+        let command: Vec<_> = current_command()?.iter().map(|arg| quote(arg)).collect();
+        let mut cmd = tmux_command();
+        cmd.arg("display-popup");
+        if let Some(client) = self.current_client() {
+            cmd.args(["-c", &client]);
+        }
+        let output = cmd.args(["-E", &command.join(" ")]).output()?;
+        if !output.status.success() {
+            return Err(TmuxError::Message(format!(
+                "Cannot display a popup: {}",
+                String::from_utf8_lossy(&output.stderr).trim_end()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Returns the arguments selecting the server, before tmux's command.
+    fn server_args(&self) -> Vec<String> {
+        match &self.socket {
+            Some(socket) => vec!["-L".into(), socket.clone()],
+            None => vec![],
+        }
+    }
+}
+
+/// Returns a [`Command`] running tmux on the server, see [`TmuxServer`].
+pub(crate) fn tmux_command() -> Command {
+    let server = TmuxServer::get();
+    let mut cmd = Command::new(server.binary());
+    cmd.args(server.server_args());
+    cmd
+}
+
+/// Returns the current executable and its arguments, to run it again.
+pub(crate) fn current_command() -> io::Result<Vec<String>> {
+    let mut command = vec![env::current_exe()?.to_string_lossy().to_string()];
+    command.extend(env::args().skip(1));
+    Ok(command)
+}
+
+/// Returns the tmux binary and the arguments selecting the server, quoted for
+/// the shell, e.g. `tmux -L work`.
+pub(crate) fn tmux_prefix() -> String {
+    let server = TmuxServer::get();
+    let mut prefix = quote(server.binary());
+    let args = server.server_args();
+    if !args.is_empty() {
+        prefix = format!("{prefix} {}", join_args(&args));
+    }
+    prefix
+}
@@ -1,12 +1,13 @@
 //! Tmux version checks
 
 use std::cmp::Ordering;
+use std::str::FromStr;
 
 /// Represents Tmux version.
 ///
 /// If for some reason `tmux -V` does not work, or can't parse the output
 /// Will return [TmuxVersion::Max].
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum TmuxVersion {
     Max,
     Version(usize, usize),
@@ -43,6 +44,19 @@ impl From<Option<&str>> for TmuxVersion {
     }
 }
 
+impl FromStr for TmuxVersion {
+    type Err = String;
+
+    /// Parses a version like `3.3a`, failing instead of returning
+    /// [TmuxVersion::Max] if it can't be parsed.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match Self::from(Some(value)) {
+            Self::Max => Err(format!("Invalid tmux version {value:?}, expected e.g. 3.3")),
+            version => Ok(version),
+        }
+    }
+}
+
 impl PartialOrd for TmuxVersion {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         match (self, other) {
@@ -79,6 +93,13 @@ mod tests {
         assert_eq!(TmuxVersion::from(next_version), TmuxVersion::Version(3, 4));
     }
 
+    #[test]
+    fn test_version_from_str() {
+        assert_eq!("3.3a".parse(), Ok(TmuxVersion::Version(3, 3)));
+        assert_eq!("2.9".parse(), Ok(TmuxVersion::Version(2, 9)));
+        assert!("latest".parse::<TmuxVersion>().is_err());
+    }
+
     #[test]
     fn test_version_compare() {
         assert!(TmuxVersion::Max > TmuxVersion::Version(2, 0));